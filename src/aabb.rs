@@ -0,0 +1,133 @@
+use std::ops::Index;
+
+use crate::{interval::Interval, ray::Ray, vec3::Point3};
+
+/// Axis-aligned bounding box, stored as one interval per axis
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    x: Interval,
+    y: Interval,
+    z: Interval,
+}
+
+impl Index<usize> for Aabb {
+    type Output = Interval;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            1 => &self.y,
+            2 => &self.z,
+            _ => &self.x,
+        }
+    }
+}
+
+impl Aabb {
+    pub const fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        Self { x, y, z }.pad_to_minimums()
+    }
+
+    /// Creates the box spanned by two corner points, in any order
+    pub const fn from_points(a: Point3, b: Point3) -> Self {
+        Self::new(
+            Interval::new(a.x().min(b.x()), a.x().max(b.x())),
+            Interval::new(a.y().min(b.y()), a.y().max(b.y())),
+            Interval::new(a.z().min(b.z()), a.z().max(b.z())),
+        )
+    }
+
+    /// Creates the smallest box enclosing both boxes
+    pub const fn surrounding(a: &Self, b: &Self) -> Self {
+        Self {
+            x: Interval::enclosing(&a.x, &b.x),
+            y: Interval::enclosing(&a.y, &b.y),
+            z: Interval::enclosing(&a.z, &b.z),
+        }
+    }
+
+    /// Avoid zero-thickness boxes for planar primitives, as they would never be hit
+    const fn pad_to_minimums(self) -> Self {
+        const DELTA: f64 = 0.0001;
+        const fn pad(interval: Interval) -> Interval {
+            if interval.size() < DELTA {
+                interval.expand(DELTA)
+            } else {
+                interval
+            }
+        }
+        Self {
+            x: pad(self.x),
+            y: pad(self.y),
+            z: pad(self.z),
+        }
+    }
+
+    pub const fn min(&self) -> Point3 {
+        Point3::new([self.x.min(), self.y.min(), self.z.min()])
+    }
+
+    pub const fn max(&self) -> Point3 {
+        Point3::new([self.x.max(), self.y.max(), self.z.max()])
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.min() + self.max())
+    }
+
+    /// Returns true if every side of the box is at a finite position, which excludes unbounded
+    /// and empty boxes
+    pub fn is_finite(&self) -> bool {
+        (0..3).all(|axis| self[axis].min().is_finite() && self[axis].max().is_finite())
+    }
+
+    /// Returns the index of the longest axis of the box
+    pub fn longest_axis(&self) -> usize {
+        if self.x.size() > self.y.size() {
+            if self.x.size() > self.z.size() { 0 } else { 2 }
+        } else if self.y.size() > self.z.size() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Returns the surface area of the box, or zero for an empty box
+    pub const fn surface_area(&self) -> f64 {
+        let (x, y, z) = (self.x.size(), self.y.size(), self.z.size());
+        if x < 0.0 || y < 0.0 || z < 0.0 {
+            return 0.0;
+        }
+        2.0 * (x * y + y * z + z * x)
+    }
+
+    /// Slab test, returns true if the ray passes through the box within `ray_time`
+    pub fn hit(&self, ray: &Ray, ray_time: Interval) -> bool {
+        self.clip(ray, ray_time).is_some()
+    }
+
+    /// Returns the part of the ray time interval during which the ray is inside the box
+    pub fn clip(&self, ray: &Ray, mut ray_time: Interval) -> Option<Interval> {
+        let origin = ray.origin();
+        let direction = ray.direction();
+        for axis in 0..3 {
+            let interval = self[axis];
+            let direction_inverse = 1.0 / direction[axis];
+
+            let t0 = (interval.min() - origin[axis]) * direction_inverse;
+            let t1 = (interval.max() - origin[axis]) * direction_inverse;
+
+            let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            ray_time = Interval::new(ray_time.min().max(t0), ray_time.max().min(t1));
+            if ray_time.max() <= ray_time.min() {
+                return None;
+            }
+        }
+        Some(ray_time)
+    }
+
+    pub const EMPTY: Aabb = Aabb {
+        x: Interval::EMPTY,
+        y: Interval::EMPTY,
+        z: Interval::EMPTY,
+    };
+}
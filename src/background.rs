@@ -0,0 +1,189 @@
+//! Light arriving from directions where a ray escapes the scene
+
+use std::{
+    f64::consts::{PI, TAU},
+    path::Path,
+};
+
+use image::{ImageResult, Rgb32FImage};
+
+use crate::{color::Color, vec3::Vec3};
+
+pub enum Background {
+    /// Same color in every direction
+    Solid(Color),
+
+    /// Vertical blend from the color straight down to the color straight up
+    Gradient { bottom: Color, top: Color },
+
+    /// Captured surroundings
+    Environment(EnvironmentMap),
+}
+
+impl Default for Background {
+    /// Blue-white sky
+    fn default() -> Self {
+        Self::Gradient {
+            bottom: Color::new([1.0; 3]),
+            top: Color::new([0.5, 0.7, 1.0]),
+        }
+    }
+}
+
+impl Background {
+    /// Returns the light arriving from the given direction
+    pub fn color(&self, direction: &Vec3) -> Color {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Gradient { bottom, top } => {
+                let a = 0.5 * (direction.unit_vector().y() + 1.0);
+                (1.0 - a) * *bottom + a * *top
+            }
+            Self::Environment(map) => map.color(direction),
+        }
+    }
+}
+
+/// Environment in equirectangular (latitude/longitude) projection, with the top row of the
+/// image straight up and the center looking down the negative z axis
+pub struct EnvironmentMap {
+    image: Rgb32FImage,
+
+    /// Rotation around the vertical axis, in radians
+    rotation: f64,
+
+    /// Scale factor for the light of the whole environment
+    intensity: f64,
+}
+
+impl EnvironmentMap {
+    /// Creates an environment map rotated by `rotation` degrees around the vertical axis
+    pub const fn new(image: Rgb32FImage, rotation: f64, intensity: f64) -> Self {
+        Self {
+            image,
+            rotation: rotation.to_radians(),
+            intensity,
+        }
+    }
+
+    /// Loads an environment map from an image file, typically `.hdr` or `.exr`
+    pub fn load(path: impl AsRef<Path>, rotation: f64, intensity: f64) -> ImageResult<Self> {
+        let image = image::open(path)?.into_rgb32f();
+        Ok(Self::new(image, rotation, intensity))
+    }
+
+    pub fn color(&self, direction: &Vec3) -> Color {
+        let direction = direction.unit_vector();
+        let longitude = direction.x().atan2(-direction.z()) - self.rotation;
+        let u = (0.5 + longitude / TAU).rem_euclid(1.0);
+        let v = direction.y().clamp(-1.0, 1.0).acos() / PI;
+
+        // Bilinear interpolation, wrapping around horizontally
+        let (width, height) = self.image.dimensions();
+        let x = u * f64::from(width) - 0.5;
+        let y = (v * f64::from(height) - 0.5).clamp(0.0, f64::from(height - 1));
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let texel = |x: f64, y: f64| {
+            let x = (x as i64).rem_euclid(i64::from(width)) as u32;
+            let y = (y as u32).min(height - 1);
+            let [r, g, b] = self.image.get_pixel(x, y).0;
+            Color::new([r.into(), g.into(), b.into()])
+        };
+        let top = (1.0 - tx) * texel(x0, y0) + tx * texel(x0 + 1.0, y0);
+        let bottom = (1.0 - tx) * texel(x0, y0 + 1.0) + tx * texel(x0 + 1.0, y0 + 1.0);
+        self.intensity * ((1.0 - ty) * top + ty * bottom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use super::*;
+
+    const WIDTH: u32 = 4;
+    const HEIGHT: u32 = 2;
+
+    /// Environment whose texel at column x and row y has the color (x, y, 1)
+    fn environment(rotation: f64, intensity: f64) -> EnvironmentMap {
+        let image = Rgb32FImage::from_fn(WIDTH, HEIGHT, |x, y| Rgb([x as f32, y as f32, 1.0]));
+        EnvironmentMap::new(image, rotation, intensity)
+    }
+
+    /// Direction seen at the image coordinates, each in `[0, 1]`
+    fn direction(u: f64, v: f64) -> Vec3 {
+        let longitude = (u - 0.5) * TAU;
+        let polar = v * PI;
+        Vec3::new([
+            polar.sin() * longitude.sin(),
+            polar.cos(),
+            -polar.sin() * longitude.cos(),
+        ])
+    }
+
+    /// Direction through the center of the texel
+    fn texel_direction(x: u32, y: u32) -> Vec3 {
+        direction(
+            (f64::from(x) + 0.5) / f64::from(WIDTH),
+            (f64::from(y) + 0.5) / f64::from(HEIGHT),
+        )
+    }
+
+    fn assert_close(actual: Color, expected: Color) {
+        assert!(
+            (actual - expected).length() < 1e-9,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn texel_centers_map_to_their_texel() {
+        let environment = environment(0.0, 1.0);
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let expected = Color::new([f64::from(x), f64::from(y), 1.0]);
+                assert_close(environment.color(&texel_direction(x, y)), expected);
+                assert_close(environment.color(&(3.0 * texel_direction(x, y))), expected);
+            }
+        }
+        // The center of the image looks down -z, between the two middle columns
+        assert_close(
+            environment.color(&Vec3::new([0.0, 0.0, -1.0])),
+            Color::new([1.5, 0.5, 1.0]),
+        );
+    }
+
+    #[test]
+    fn intensity_scales_the_light() {
+        let environment = environment(0.0, 2.5);
+        assert_close(
+            environment.color(&texel_direction(3, 1)),
+            Color::new([7.5, 2.5, 2.5]),
+        );
+    }
+
+    #[test]
+    fn seam_blends_the_first_and_last_columns() {
+        let environment = environment(0.0, 1.0);
+        // Looking down +z is at u = 0, halfway between the centers of columns 3 and 0
+        let expected = Color::new([1.5, 0.5, 1.0]);
+        assert_close(environment.color(&Vec3::new([0.0, 0.0, 1.0])), expected);
+        for u in [1e-9, 1.0 - 1e-9] {
+            let color = environment.color(&direction(u, 0.5));
+            assert!((color - expected).length() < 1e-6, "{u} {color:?}");
+        }
+    }
+
+    #[test]
+    fn rotation_turns_the_environment_around_the_vertical() {
+        let environment = environment(90.0, 1.0);
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let expected = Color::new([f64::from(x), f64::from(y), 1.0]);
+                let turned = texel_direction((x + 1) % WIDTH, y);
+                assert_close(environment.color(&turned), expected);
+            }
+        }
+    }
+}
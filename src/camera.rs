@@ -1,21 +1,25 @@
-use std::{
-    array,
-    sync::atomic::{AtomicU32, Ordering},
-};
+use std::sync::atomic::{AtomicU32, Ordering};
 
-use image::{ImageBuffer, Rgb};
-use rand::random;
+use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
 
 use crate::{
+    background::Background,
     color::Color,
-    hittable::{Hittable as _, list::List as HittableList},
+    framebuffer::Framebuffer,
+    hittable::{Hittable, list::List},
+    integrator::PathTracer,
     interval::Interval,
     ray::Ray,
+    rng::{self, random, random_range},
+    spectrum::Wavelengths,
     vec3::{Point3, Vec3},
 };
 
 #[expect(dead_code)]
 pub struct Camera {
+    /// Ratio of image width over height
+    aspect_ratio: f64,
+
     /// Rendered image width in pixel count
     image_width: u32,
 
@@ -43,6 +47,9 @@ pub struct Camera {
     /// Maximum number of ray bounces into scene
     max_depth: u8,
 
+    /// Vertical view angle (field of view) in degrees
+    vfov: f64,
+
     /// Point camera is looking from
     look_from: Point3,
 
@@ -68,6 +75,22 @@ pub struct Camera {
 
     /// Defocus disk vertical radius
     defocus_disk_v: Vec3,
+
+    /// Seed for the random samples, renders with the same seed are identical
+    seed: Option<u64>,
+
+    /// Light arriving from rays that escape the scene
+    background: Background,
+
+    /// Times the shutter opens and closes, each ray is sent at a random moment in between
+    shutter: Interval,
+
+    /// Number of bounces after which paths may be terminated by Russian roulette, while
+    /// `max_depth` always ends them
+    roulette_depth: u8,
+
+    /// Whether to trace light at sampled wavelengths instead of red, green and blue
+    spectral: bool,
 }
 
 impl Default for Camera {
@@ -135,6 +158,7 @@ impl Camera {
         // Calculate the camera defocus disk basis
         let defocus_radius = focus_distance * (defocus_angle / 2.0).to_radians().tan();
         Self {
+            aspect_ratio,
             image_width,
             image_height,
             center,
@@ -144,6 +168,7 @@ impl Camera {
             samples_per_pixel,
             pixel_samples_scale: 1.0 / f64::from(samples_per_pixel),
             max_depth,
+            vfov,
             look_from,
             look_at,
             relative_up,
@@ -154,9 +179,93 @@ impl Camera {
             focus_distance,
             defocus_disk_u: u * defocus_radius,
             defocus_disk_v: v * defocus_radius,
+            seed: None,
+            background: Background::default(),
+            shutter: Interval::new(0.0, 1.0),
+            roulette_depth: 3,
+            spectral: false,
+        }
+    }
+
+    pub const fn image_width(&self) -> u32 {
+        self.image_width
+    }
+
+    pub const fn samples_per_pixel(&self) -> u16 {
+        self.samples_per_pixel
+    }
+
+    pub const fn max_depth(&self) -> u8 {
+        self.max_depth
+    }
+
+    /// Returns the camera with a different image width, keeping the aspect ratio
+    pub fn with_image_width(self, image_width: u32) -> Self {
+        Self {
+            seed: self.seed,
+            background: self.background,
+            shutter: self.shutter,
+            roulette_depth: self.roulette_depth,
+            spectral: self.spectral,
+            ..Self::new(
+                self.aspect_ratio,
+                image_width,
+                self.samples_per_pixel,
+                self.max_depth,
+                self.vfov,
+                self.look_from,
+                self.look_at,
+                self.relative_up,
+                self.defocus_angle,
+                self.focus_distance,
+            )
+        }
+    }
+
+    /// Returns the camera with a different aspect ratio, keeping the image width
+    pub fn with_aspect_ratio(self, aspect_ratio: f64) -> Self {
+        Self {
+            aspect_ratio,
+            ..self
+        }
+        .with_image_width(self.image_width)
+    }
+
+    pub fn with_samples_per_pixel(self, samples_per_pixel: u16) -> Self {
+        Self {
+            samples_per_pixel,
+            pixel_samples_scale: 1.0 / f64::from(samples_per_pixel),
+            ..self
+        }
+    }
+
+    pub fn with_max_depth(self, max_depth: u8) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    pub fn with_seed(self, seed: Option<u64>) -> Self {
+        Self { seed, ..self }
+    }
+
+    pub fn with_background(self, background: Background) -> Self {
+        Self { background, ..self }
+    }
+
+    pub fn with_shutter(self, shutter: Interval) -> Self {
+        Self { shutter, ..self }
+    }
+
+    pub fn with_roulette_depth(self, roulette_depth: u8) -> Self {
+        Self {
+            roulette_depth,
+            ..self
         }
     }
 
+    pub fn with_spectral(self, spectral: bool) -> Self {
+        Self { spectral, ..self }
+    }
+
     fn sample_square() -> Vec3 {
         Vec3::new([random::<f64>() - 0.5, random::<f64>() - 0.5, 0.0])
     }
@@ -168,7 +277,7 @@ impl Camera {
     }
 
     // Construct a camera ray originating from the defocus disk and directed at randomly
-    // sampled point around the pixel location x, y.
+    // sampled point around the pixel location x, y, at a random moment the shutter is open.
     fn get_ray(&self, x: u32, y: u32) -> Ray {
         let offset = Self::sample_square();
         let pixel_sample = self.pixel_origin_location
@@ -182,42 +291,89 @@ impl Camera {
         };
         let ray_direction = pixel_sample - ray_origin;
 
-        Ray::new(ray_origin, ray_direction)
+        let shutter_time = if self.shutter.size() > 0.0 {
+            random_range(self.shutter.min()..self.shutter.max())
+        } else {
+            self.shutter.min()
+        };
+
+        Ray::new(ray_origin, ray_direction, shutter_time)
     }
 
-    fn ray_color(ray: &Ray, world: &HittableList, depth: u8) -> Color {
-        if depth == 0 {
-            return Color::default();
+    /// Returns the linear color of one sample of the light arriving through pixel x, y
+    fn sample(&self, tracer: &PathTracer, x: u32, y: u32) -> Color {
+        let ray = self.get_ray(x, y);
+        if !self.spectral {
+            return tracer.radiance(ray);
         }
-        if let Some(record) = world.hit(ray, Interval::new(0.001, f64::INFINITY)) {
-            return record.material().scatter(ray, &record).map_or_else(
-                Color::default,
-                |(scattered, attenuation)| {
-                    attenuation * Self::ray_color(&scattered, world, depth - 1)
-                },
-            );
-        }
-        let unit_direction = ray.direction().unit_vector();
-        let a = 0.5 * (unit_direction.y() + 1.0);
-        (1.0 - a) * Color::new(array::from_fn(|_| 1.0)) + a * Color::new([0.5, 0.7, 1.0])
+        let wavelengths = Wavelengths::sample();
+        wavelengths.to_rgb(tracer.radiance(ray.with_wavelengths(Some(wavelengths))))
     }
 
-    pub fn render(&self, world: &HittableList) {
+    /// Renders the world into a framebuffer of linear colors, sampling the lights directly
+    pub fn render(&self, world: &(dyn Hittable + Sync + Send), lights: &List) -> Framebuffer {
+        let tracer = PathTracer::new(
+            world,
+            lights,
+            &self.background,
+            self.roulette_depth,
+            self.max_depth,
+        );
         let progress = AtomicU32::new(0);
-        ImageBuffer::from_par_fn(self.image_width, self.image_height, |x, y| {
-            if x == 0 {
-                eprint!(
-                    "{:02}%\r",
-                    progress.fetch_add(1, Ordering::Relaxed) * 100 / self.image_height
-                );
-            }
-            let pixel_color = (0..self.samples_per_pixel)
-                .map(|_| Self::ray_color(&self.get_ray(x, y), world, self.max_depth))
-                .sum::<Color>()
-                * self.pixel_samples_scale;
-            Rgb::from(pixel_color)
-        })
-        .save("image.png")
-        .unwrap();
+        let pixels = (0..self.image_height)
+            .into_par_iter()
+            .flat_map(|y| (0..self.image_width).into_par_iter().map(move |x| (x, y)))
+            .map(|(x, y)| {
+                if x == 0 {
+                    eprint!(
+                        "{:02}%\r",
+                        progress.fetch_add(1, Ordering::Relaxed) * 100 / self.image_height
+                    );
+                }
+                if let Some(seed) = self.seed {
+                    // Give every pixel its own sequence, independent of the thread rendering it
+                    rng::reseed(rng::mix_seed([seed, x.into(), y.into()]));
+                }
+                (0..self.samples_per_pixel)
+                    .map(|_| self.sample(&tracer, x, y))
+                    .sum::<Color>()
+                    * self.pixel_samples_scale
+            })
+            .collect();
+        Framebuffer::new(self.image_width, self.image_height, pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_returns_framebuffer_of_image_size() {
+        let camera = Camera::default().with_image_width(16).with_seed(Some(1));
+        let framebuffer = camera.render(&List::default(), &List::default());
+        assert_eq!((framebuffer.width(), framebuffer.height()), (16, 16));
+        assert_eq!(framebuffer.pixels().len(), 16 * 16);
+    }
+
+    #[test]
+    fn spectral_render_keeps_gray() {
+        let camera = Camera::default()
+            .with_image_width(8)
+            .with_samples_per_pixel(500)
+            .with_seed(Some(5))
+            .with_background(Background::Solid(Color::new([0.5; 3])))
+            .with_spectral(true);
+        let framebuffer = camera.render(&List::default(), &List::default());
+        let mean =
+            framebuffer.pixels().iter().copied().sum::<Color>() / framebuffer.pixels().len() as f64;
+        assert!((mean - Color::new([0.5; 3])).length() < 0.01, "{mean:?}");
+    }
+
+    #[test]
+    fn seeded_renders_are_identical() {
+        let camera = Camera::default().with_image_width(8).with_seed(Some(42));
+        let world = List::default();
+        assert_eq!(camera.render(&world, &world), camera.render(&world, &world));
     }
 }
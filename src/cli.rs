@@ -0,0 +1,293 @@
+//! Command-line argument parsing for the renderer binary
+
+use std::{
+    error,
+    ffi::OsString,
+    fmt::{self, Display},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use crate::tone_map::{Operator, ToneMapping};
+
+pub const USAGE: &str = "\
+Usage: ray_tracing_in_one_weekend [OPTIONS] [SCENE]
+
+Renders the TOML scene file SCENE, or the built-in random spheres scene if omitted.
+
+Options:
+  -o, --output <PATH>        Output image path, .exr and .hdr store linear colors
+                             [default: image.png]
+  -w, --width <PIXELS>       Image width, overriding the scene
+  -a, --aspect <RATIO>       Aspect ratio as a number or W:H, overriding the scene
+  -s, --samples <COUNT>      Samples per pixel, overriding the scene
+  -d, --max-depth <COUNT>    Maximum ray bounces, overriding the scene
+  -j, --threads <COUNT>      Number of render threads [default: all cores]
+      --seed <SEED>          Seed for reproducible renders
+      --tone-map <OPERATOR>  Tone mapping for 8-bit output: clamp, reinhard, aces or
+                             extended-reinhard[:WHITE] [default: clamp]
+      --exposure <EV>        Exposure compensation in stops [default: 0]
+      --preview              Quick low quality render, explicit options take precedence
+      --spectral             Render with sampled wavelengths, overriding the scene
+  -h, --help                 Print this help";
+
+/// Image width used by `--preview`, unless the scene is already smaller
+const PREVIEW_WIDTH: u32 = 400;
+
+/// Samples per pixel used by `--preview`
+const PREVIEW_SAMPLES_PER_PIXEL: u16 = 8;
+
+/// Maximum ray bounces used by `--preview`
+const PREVIEW_MAX_DEPTH: u8 = 8;
+
+#[derive(Debug)]
+pub struct UsageError(String);
+
+impl Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl error::Error for UsageError {}
+
+#[derive(Debug)]
+pub struct Arguments {
+    pub scene: Option<PathBuf>,
+    pub output: PathBuf,
+    pub width: Option<u32>,
+    pub aspect_ratio: Option<f64>,
+    pub samples_per_pixel: Option<u16>,
+    pub max_depth: Option<u8>,
+    pub threads: Option<usize>,
+    pub seed: Option<u64>,
+    pub tone_mapping: ToneMapping,
+    pub preview: bool,
+    pub spectral: bool,
+}
+
+impl Default for Arguments {
+    fn default() -> Self {
+        Self {
+            scene: None,
+            output: PathBuf::from("image.png"),
+            width: None,
+            aspect_ratio: None,
+            samples_per_pixel: None,
+            max_depth: None,
+            threads: None,
+            seed: None,
+            tone_mapping: ToneMapping::default(),
+            preview: false,
+            spectral: false,
+        }
+    }
+}
+
+impl Arguments {
+    /// Parses the arguments, without the program name. Returns `None` if help was requested.
+    pub fn parse(
+        arguments: impl IntoIterator<Item = OsString>,
+    ) -> Result<Option<Self>, UsageError> {
+        let mut parsed = Self::default();
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            let Some(text) = argument.to_str() else {
+                // Only paths may be non UTF-8
+                parsed.set_scene(argument.into())?;
+                continue;
+            };
+            if !text.starts_with('-') || text == "-" {
+                parsed.set_scene(argument.into())?;
+                continue;
+            }
+
+            // Support both `--option value` and `--option=value`
+            let (option, inline_value) = match text.split_once('=') {
+                Some((option, value)) if text.starts_with("--") => (option, Some(value)),
+                _ => (text, None),
+            };
+            let mut value = || -> Result<OsString, UsageError> {
+                inline_value
+                    .map(OsString::from)
+                    .or_else(|| arguments.next())
+                    .ok_or_else(|| UsageError(format!("missing value for `{option}`")))
+            };
+            match option {
+                "-h" | "--help" => return Ok(None),
+                "-o" | "--output" => parsed.output = value()?.into(),
+                "-w" | "--width" => parsed.width = Some(parse_positive(option, &value()?)?),
+                "-a" | "--aspect" => parsed.aspect_ratio = Some(parse_aspect_ratio(&value()?)?),
+                "-s" | "--samples" => {
+                    parsed.samples_per_pixel = Some(parse_positive(option, &value()?)?);
+                }
+                "-d" | "--max-depth" => {
+                    parsed.max_depth = Some(parse_positive(option, &value()?)?);
+                }
+                "-j" | "--threads" => parsed.threads = Some(parse_positive(option, &value()?)?),
+                "--seed" => parsed.seed = Some(parse_value(option, &value()?)?),
+                "--tone-map" => {
+                    let value = value()?;
+                    parsed.tone_mapping.operator = value
+                        .to_str()
+                        .ok_or_else(|| UsageError(format!("invalid value for `{option}`")))?
+                        .parse::<Operator>()
+                        .map_err(|error| UsageError(format!("{error} for `{option}`")))?;
+                }
+                "--exposure" => {
+                    parsed.tone_mapping.exposure = parse_value(option, &value()?)?;
+                    if !parsed.tone_mapping.exposure.is_finite() {
+                        return Err(UsageError(format!("`{option}` must be finite")));
+                    }
+                }
+                "--preview" if inline_value.is_none() => parsed.preview = true,
+                "--spectral" if inline_value.is_none() => parsed.spectral = true,
+                _ => return Err(UsageError(format!("unknown option `{text}`"))),
+            }
+        }
+
+        if parsed.preview {
+            parsed.samples_per_pixel = parsed.samples_per_pixel.or(Some(PREVIEW_SAMPLES_PER_PIXEL));
+            parsed.max_depth = parsed.max_depth.or(Some(PREVIEW_MAX_DEPTH));
+        }
+        Ok(Some(parsed))
+    }
+
+    /// Image width to use for a scene of the given width
+    pub fn image_width(&self, scene_width: u32) -> u32 {
+        self.width.unwrap_or(if self.preview {
+            scene_width.min(PREVIEW_WIDTH)
+        } else {
+            scene_width
+        })
+    }
+
+    fn set_scene(&mut self, scene: PathBuf) -> Result<(), UsageError> {
+        if let Some(previous) = &self.scene {
+            return Err(UsageError(format!(
+                "more than one scene given: `{}` and `{}`",
+                previous.display(),
+                scene.display()
+            )));
+        }
+        self.scene = Some(scene);
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(option: &str, value: &OsString) -> Result<T, UsageError> {
+    value
+        .to_str()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| {
+            UsageError(format!(
+                "invalid value `{}` for `{option}`",
+                value.to_string_lossy()
+            ))
+        })
+}
+
+fn parse_positive<T: FromStr + Default + PartialOrd>(
+    option: &str,
+    value: &OsString,
+) -> Result<T, UsageError> {
+    let parsed = parse_value(option, value)?;
+    if parsed > T::default() {
+        Ok(parsed)
+    } else {
+        Err(UsageError(format!("`{option}` must be positive")))
+    }
+}
+
+/// Parses an aspect ratio written as a number, like `1.5`, or as a ratio, like `16:9`
+fn parse_aspect_ratio(value: &OsString) -> Result<f64, UsageError> {
+    let option = "--aspect";
+    let aspect_ratio = match value.to_str().and_then(|value| value.split_once(':')) {
+        Some((width, height)) => {
+            parse_positive::<f64>(option, &width.into())?
+                / parse_positive::<f64>(option, &height.into())?
+        }
+        None => parse_positive(option, value)?,
+    };
+    if aspect_ratio.is_finite() {
+        Ok(aspect_ratio)
+    } else {
+        Err(UsageError(format!("`{option}` must be finite")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Option<Arguments>, UsageError> {
+        Arguments::parse(arguments.iter().map(OsString::from))
+    }
+
+    fn parsed(arguments: &[&str]) -> Arguments {
+        parse(arguments).unwrap().unwrap()
+    }
+
+    fn error(arguments: &[&str]) -> String {
+        parse(arguments).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn options_take_separate_or_inline_values() {
+        let arguments = parsed(&["scene.toml", "-w", "640", "--samples=16", "--aspect", "4:3"]);
+        assert_eq!(arguments.scene, Some(PathBuf::from("scene.toml")));
+        assert_eq!(arguments.width, Some(640));
+        assert_eq!(arguments.samples_per_pixel, Some(16));
+        assert_eq!(arguments.aspect_ratio, Some(4.0 / 3.0));
+        assert!(parse(&["-w", "10", "--help"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn unknown_options_are_rejected() {
+        assert_eq!(error(&["--frobnicate"]), "unknown option `--frobnicate`");
+        assert_eq!(error(&["--preview=yes"]), "unknown option `--preview=yes`");
+        assert_eq!(
+            error(&["a.toml", "b.toml"]),
+            "more than one scene given: `a.toml` and `b.toml`"
+        );
+    }
+
+    #[test]
+    fn missing_values_are_rejected() {
+        assert_eq!(error(&["--output"]), "missing value for `--output`");
+        assert_eq!(error(&["-w"]), "missing value for `-w`");
+    }
+
+    #[test]
+    fn values_must_be_numbers() {
+        assert_eq!(error(&["-w", "wide"]), "invalid value `wide` for `-w`");
+        assert_eq!(error(&["--seed=x"]), "invalid value `x` for `--seed`");
+        assert_eq!(error(&["--samples", "0"]), "`--samples` must be positive");
+        assert_eq!(error(&["--aspect", "16:0"]), "`--aspect` must be positive");
+        assert_eq!(error(&["--exposure", "inf"]), "`--exposure` must be finite");
+    }
+
+    #[test]
+    fn explicit_options_take_precedence_over_preview() {
+        let preview = parsed(&["--preview"]);
+        assert_eq!(preview.samples_per_pixel, Some(PREVIEW_SAMPLES_PER_PIXEL));
+        assert_eq!(preview.max_depth, Some(PREVIEW_MAX_DEPTH));
+        assert_eq!(preview.image_width(1200), PREVIEW_WIDTH);
+        assert_eq!(preview.image_width(200), 200);
+
+        let explicit = parsed(&["-s", "64", "--preview", "-d", "20", "-w", "800"]);
+        assert_eq!(explicit.samples_per_pixel, Some(64));
+        assert_eq!(explicit.max_depth, Some(20));
+        assert_eq!(explicit.image_width(1200), 800);
+    }
+
+    #[test]
+    fn spectral_is_a_flag() {
+        assert!(!parsed(&[]).spectral);
+        assert!(parsed(&["--spectral"]).spectral);
+        assert_eq!(
+            error(&["--spectral=true"]),
+            "unknown option `--spectral=true`"
+        );
+    }
+}
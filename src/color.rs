@@ -6,18 +6,80 @@ use crate::vec3::Vec3;
 
 pub type Color = Vec3;
 
-fn linear_to_gamma(linear_component: f64) -> f64 {
-    if linear_component > 0.0 {
-        linear_component.sqrt()
+/// Relative luminance of a linear sRGB color
+pub const fn luminance(color: Color) -> f64 {
+    color.dot(&Vec3::new([0.2126, 0.7152, 0.0722]))
+}
+
+/// sRGB opto-electronic transfer function, encoding a linear component for display
+pub fn linear_to_srgb(linear_component: f64) -> f64 {
+    if linear_component <= 0.0 {
+        0.0
+    } else if linear_component <= 0.003_130_8 {
+        12.92 * linear_component
     } else {
+        1.055 * linear_component.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Inverse of [`linear_to_srgb`], decoding a display encoded component to linear
+pub fn srgb_to_linear(srgb_component: f64) -> f64 {
+    if srgb_component <= 0.0 {
         0.0
+    } else if srgb_component <= 0.040_45 {
+        srgb_component / 12.92
+    } else {
+        ((srgb_component + 0.055) / 1.055).powf(2.4)
     }
 }
 
+/// Encodes a color in the displayable range to 8-bit sRGB, clipping anything outside of it
 impl From<Color> for Rgb<u8> {
     fn from(value: Color) -> Self {
         Self(array::from_fn(|i| {
-            (linear_to_gamma(value[i]).clamp(0.0, 0.999) * 256.0) as u8
+            (linear_to_srgb(value[i]).clamp(0.0, 1.0) * 255.0).round() as u8
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_functions_meet_at_the_breakpoint() {
+        let encoded = linear_to_srgb(0.003_130_8);
+        assert!((encoded - 0.040_45).abs() < 1e-5, "{encoded}");
+        // Both pieces agree around the breakpoint in each direction
+        for linear in [0.003_130_8 - 1e-9, 0.003_130_8 + 1e-9] {
+            assert!((linear_to_srgb(linear) - encoded).abs() < 1e-6);
+        }
+        for srgb in [0.040_45 - 1e-9, 0.040_45 + 1e-9] {
+            assert!((srgb_to_linear(srgb) - 0.003_130_8).abs() < 1e-6);
+        }
+        assert_eq!(linear_to_srgb(0.0), 0.0);
+        assert_eq!(linear_to_srgb(-1.0), 0.0);
+        assert!((linear_to_srgb(1.0) - 1.0).abs() < 1e-12);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn transfer_functions_round_trip() {
+        for i in 0..=1000 {
+            let linear = f64::from(i) / 1000.0;
+            let decoded = srgb_to_linear(linear_to_srgb(linear));
+            assert!((decoded - linear).abs() < 1e-12, "{linear} {decoded}");
+            let encoded = linear_to_srgb(srgb_to_linear(linear));
+            assert!((encoded - linear).abs() < 1e-12, "{linear} {encoded}");
+        }
+    }
+
+    #[test]
+    fn colors_are_encoded_to_8_bits() {
+        assert_eq!(
+            Rgb::<u8>::from(Color::new([0.0, 1.0, 2.0])),
+            Rgb([0, 255, 255])
+        );
+        assert_eq!(Rgb::<u8>::from(Color::new([0.5; 3])), Rgb([188; 3]));
+    }
+}
@@ -0,0 +1,87 @@
+use image::{Rgb, Rgb32FImage, RgbImage};
+
+use crate::{color::Color, tone_map::ToneMapping};
+
+/// Rendered image as linear, high dynamic range colors, before any gamma or clamping
+#[derive(Debug, Clone, PartialEq)]
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+
+    /// Pixels in row-major order, starting at the top left
+    pixels: Vec<Color>,
+}
+
+impl Framebuffer {
+    /// Creates a framebuffer from pixels in row-major order.
+    ///
+    /// # Panics
+    /// Panics if the number of pixels doesn't match the dimensions.
+    pub fn new(width: u32, height: u32, pixels: Vec<Color>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize,
+            "pixel count doesn't match a {width}x{height} framebuffer"
+        );
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Converts to a linear 32-bit float image, keeping the full dynamic range
+    pub fn to_rgb32f(&self) -> Rgb32FImage {
+        Rgb32FImage::from_fn(self.width, self.height, |x, y| {
+            let color = self.pixel(x, y);
+            Rgb([color.x() as f32, color.y() as f32, color.z() as f32])
+        })
+    }
+
+    /// Converts to an 8-bit sRGB image, tone mapping the linear colors first
+    pub fn to_rgb8(&self, tone_mapping: &ToneMapping) -> RgbImage {
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            Rgb::from(tone_mapping.apply(self.pixel(x, y)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn float_images_keep_the_linear_colors() {
+        let framebuffer = Framebuffer::new(
+            2,
+            1,
+            vec![Color::new([0.25, 1.5, 0.0]), Color::new([8.0, 0.5, 100.0])],
+        );
+        let image = framebuffer.to_rgb32f();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0), &Rgb([0.25, 1.5, 0.0]));
+        assert_eq!(image.get_pixel(1, 0), &Rgb([8.0, 0.5, 100.0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "pixel count")]
+    fn pixel_count_must_match() {
+        Framebuffer::new(2, 2, vec![Color::default(); 3]);
+    }
+}
@@ -1,14 +1,28 @@
-use std::sync::Arc;
+use std::{fmt::Debug, sync::Arc};
 
 use crate::{
+    aabb::Aabb,
     interval::Interval,
     material::Material,
     ray::Ray,
     vec3::{Point3, Vec3},
 };
 
+pub mod bvh;
+pub mod constant_medium;
+pub mod cuboid;
+pub mod disk;
+pub mod heterogeneous_medium;
 pub mod list;
+pub mod mesh;
+pub mod moving_sphere;
+pub mod plane;
+pub mod quad;
 pub mod sphere;
+#[cfg(test)]
+mod testing;
+pub mod transform;
+pub mod triangle;
 
 pub struct HitRecord {
     point: Point3,
@@ -16,6 +30,9 @@ pub struct HitRecord {
     material: Arc<dyn Material>,
     time: f64,
     front_face: bool,
+
+    /// Surface texture coordinates of the hit point
+    uv: [f64; 2],
 }
 
 impl HitRecord {
@@ -40,9 +57,29 @@ impl HitRecord {
             material,
             time,
             front_face,
+            uv: [0.0; 2],
         }
     }
 
+    /// Moves the hit to another coordinate frame, keeping which face was hit.
+    /// NOTE: the parameter `normal` is assumed to have unit length and to face the ray.
+    pub fn transformed(self, point: Point3, normal: Vec3) -> Self {
+        Self {
+            point,
+            normal,
+            ..self
+        }
+    }
+
+    pub fn with_material(self, material: Arc<dyn Material>) -> Self {
+        Self { material, ..self }
+    }
+
+    /// Sets the surface texture coordinates of the hit point
+    pub fn with_uv(self, uv: [f64; 2]) -> Self {
+        Self { uv, ..self }
+    }
+
     pub const fn point(&self) -> &Point3 {
         &self.point
     }
@@ -59,11 +96,71 @@ impl HitRecord {
         self.front_face
     }
 
+    pub const fn uv(&self) -> [f64; 2] {
+        self.uv
+    }
+
     pub fn material(&self) -> &dyn Material {
         self.material.as_ref()
     }
 }
 
+impl PartialEq for HitRecord {
+    fn eq(&self, other: &Self) -> bool {
+        self.point == other.point
+            && self.normal == other.normal
+            && Arc::ptr_eq(&self.material, &other.material)
+            && self.time == other.time
+            && self.front_face == other.front_face
+            && self.uv == other.uv
+    }
+}
+
+impl Debug for HitRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HitRecord")
+            .field("point", &self.point)
+            .field("normal", &self.normal)
+            .field("time", &self.time)
+            .field("front_face", &self.front_face)
+            .field("uv", &self.uv)
+            .finish_non_exhaustive()
+    }
+}
+
 pub trait Hittable {
     fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord>;
+
+    /// Returns a box enclosing everything this object can be hit at
+    fn bounding_box(&self) -> Aabb;
+
+    /// Returns the density, per unit solid angle, of sampling the direction from the origin
+    /// with [`Hittable::random`]. Zero for objects that can't be sampled.
+    fn pdf_value(&self, _origin: &Point3, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// Returns a random direction from the origin toward the object, used to sample lights
+    fn random(&self, _origin: &Point3) -> Vec3 {
+        Vec3::new([1.0, 0.0, 0.0])
+    }
+}
+
+/// Shared objects, so that lights can be both in the world and sampled directly
+impl<T: Hittable + ?Sized> Hittable for Arc<T> {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        self.as_ref().hit(ray, ray_time)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.as_ref().bounding_box()
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        self.as_ref().pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        self.as_ref().random(origin)
+    }
 }
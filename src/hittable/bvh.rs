@@ -0,0 +1,250 @@
+use crate::{aabb::Aabb, interval::Interval, ray::Ray};
+
+use super::{HitRecord, Hittable, list::List};
+
+/// Number of centroid buckets evaluated by the surface area heuristic
+const BUCKET_COUNT: usize = 12;
+
+enum Children {
+    Leaf(Box<dyn Hittable + Sync + Send>),
+    Split(Box<BvhNode>, Box<BvhNode>),
+}
+
+/// Bounding volume hierarchy, a binary tree of bounding boxes that lets a ray skip every object
+/// whose box it misses. Unbounded objects, like infinite planes, can't be placed in the tree and
+/// are kept in a list next to it.
+pub struct BvhNode {
+    children: Children,
+    bounding_box: Aabb,
+}
+
+impl BvhNode {
+    pub fn new(objects: Vec<Box<dyn Hittable + Sync + Send>>) -> Self {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = objects
+            .into_iter()
+            .partition(|object| object.bounding_box().is_finite());
+        let tree = Self::build(bounded);
+        if unbounded.is_empty() {
+            return tree;
+        }
+        let mut list = List::from(unbounded);
+        list.add(Box::new(tree));
+        Self::leaf(Box::new(list))
+    }
+
+    /// Builds the tree of objects with finite bounding boxes
+    fn build(mut objects: Vec<Box<dyn Hittable + Sync + Send>>) -> Self {
+        match objects.len() {
+            0 => Self::leaf(Box::new(List::default())),
+            1 => Self::leaf(objects.pop().unwrap()),
+            _ => {
+                let (left, right) = Self::split(objects);
+                let (left, right) = (Self::build(left), Self::build(right));
+                Self {
+                    bounding_box: Aabb::surrounding(&left.bounding_box, &right.bounding_box),
+                    children: Children::Split(Box::new(left), Box::new(right)),
+                }
+            }
+        }
+    }
+
+    fn leaf(object: Box<dyn Hittable + Sync + Send>) -> Self {
+        Self {
+            bounding_box: object.bounding_box(),
+            children: Children::Leaf(object),
+        }
+    }
+
+    /// Splits the objects in two groups along the longest axis of their centroids, choosing
+    /// the split position with the lowest surface area heuristic cost.
+    #[expect(clippy::type_complexity)]
+    fn split(
+        mut objects: Vec<Box<dyn Hittable + Sync + Send>>,
+    ) -> (
+        Vec<Box<dyn Hittable + Sync + Send>>,
+        Vec<Box<dyn Hittable + Sync + Send>>,
+    ) {
+        let centroid = |object: &(dyn Hittable + Sync + Send)| object.bounding_box().centroid();
+        let centroid_bounds = objects.iter().fold(Aabb::EMPTY, |bounds, object| {
+            let centroid = centroid(object.as_ref());
+            Aabb::surrounding(&bounds, &Aabb::from_points(centroid, centroid))
+        });
+        let axis = centroid_bounds.longest_axis();
+        let axis_interval = centroid_bounds[axis];
+
+        let bucket_of = |object: &(dyn Hittable + Sync + Send)| {
+            let offset = (centroid(object)[axis] - axis_interval.min()) / axis_interval.size();
+            ((offset * BUCKET_COUNT as f64) as usize).min(BUCKET_COUNT - 1)
+        };
+
+        let mut buckets = [(0_usize, Aabb::EMPTY); BUCKET_COUNT];
+        for object in &objects {
+            let bucket = &mut buckets[bucket_of(object.as_ref())];
+            bucket.0 += 1;
+            bucket.1 = Aabb::surrounding(&bucket.1, &object.bounding_box());
+        }
+
+        let side_cost = |buckets: &[(usize, Aabb)]| {
+            let (count, bounds) = buckets.iter().fold(
+                (0, Aabb::EMPTY),
+                |(count, bounds), (bucket_count, bucket_bounds)| {
+                    (
+                        count + bucket_count,
+                        Aabb::surrounding(&bounds, bucket_bounds),
+                    )
+                },
+            );
+            (count, count as f64 * bounds.surface_area())
+        };
+        let best_split = (1..BUCKET_COUNT)
+            .filter_map(|split| {
+                let (left_count, left_cost) = side_cost(&buckets[..split]);
+                let (right_count, right_cost) = side_cost(&buckets[split..]);
+                (left_count > 0 && right_count > 0).then_some((split, left_cost + right_cost))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((split, _)) = best_split {
+            objects
+                .into_iter()
+                .partition(|object| bucket_of(object.as_ref()) < split)
+        } else {
+            // All centroids fall in the same bucket, so fall back to a median split
+            objects
+                .sort_by(|a, b| centroid(a.as_ref())[axis].total_cmp(&centroid(b.as_ref())[axis]));
+            let right = objects.split_off(objects.len() / 2);
+            (objects, right)
+        }
+    }
+}
+
+impl From<List> for BvhNode {
+    fn from(value: List) -> Self {
+        Self::new(value.into_objects())
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        if !self.bounding_box.hit(ray, ray_time) {
+            return None;
+        }
+        match &self.children {
+            Children::Leaf(object) => object.hit(ray, ray_time),
+            Children::Split(left, right) => {
+                let left_record = left.hit(ray, ray_time);
+                let right_ray_time = left_record
+                    .as_ref()
+                    .map_or(ray_time, |record| ray_time.with_max(record.time()));
+                right.hit(ray, right_ray_time).or(left_record)
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        color::Color,
+        hittable::{plane::Plane, sphere::Sphere},
+        material::{Material, lambertian::Lambertian},
+        rng::random_range,
+        vec3::{Point3, Vec3},
+    };
+
+    fn random_ray() -> Ray {
+        Ray::new(Point3::random(-15.0..15.0), Vec3::random_unit_vector(), 0.0)
+    }
+
+    /// Builds the same random spheres into a flat list and a hierarchy, so both can be compared
+    fn list_and_bvh(count: usize) -> (List, BvhNode) {
+        let material: Arc<dyn Material + Sync + Send> =
+            Arc::new(Lambertian::new(Color::new([0.5; 3])));
+        let spheres: Vec<_> = (0..count)
+            .map(|_| (Point3::random(-10.0..10.0), random_range(0.05..1.0)))
+            .collect();
+        let build = || -> Vec<Box<dyn Hittable + Sync + Send>> {
+            spheres
+                .iter()
+                .map(|&(center, radius)| -> Box<dyn Hittable + Sync + Send> {
+                    Box::new(Sphere::new(center, radius, material.clone()))
+                })
+                .collect()
+        };
+        (List::from(build()), BvhNode::new(build()))
+    }
+
+    #[test]
+    fn matches_list_hits() {
+        for count in [1, 2, 3, 17, 500] {
+            let (list, bvh) = list_and_bvh(count);
+            for _ in 0..2000 {
+                let ray = random_ray();
+                let ray_time = Interval::new(0.001, f64::INFINITY);
+                assert_eq!(list.hit(&ray, ray_time), bvh.hit(&ray, ray_time));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_list_hits_in_limited_interval() {
+        let (list, bvh) = list_and_bvh(200);
+        for _ in 0..2000 {
+            let ray = random_ray();
+            let ray_time = Interval::new(random_range(0.0..5.0), random_range(5.0..20.0));
+            assert_eq!(list.hit(&ray, ray_time), bvh.hit(&ray, ray_time));
+        }
+    }
+
+    #[test]
+    fn bounding_box_encloses_objects() {
+        let (list, bvh) = list_and_bvh(100);
+        assert_eq!(list.bounding_box(), bvh.bounding_box());
+    }
+
+    #[test]
+    fn unbounded_objects_are_kept_out_of_the_tree() {
+        let material: Arc<dyn Material + Sync + Send> =
+            Arc::new(Lambertian::new(Color::new([0.5; 3])));
+        let spheres: Vec<_> = (0..100)
+            .map(|_| (Point3::random(-10.0..10.0), random_range(0.05..1.0)))
+            .collect();
+        let build = || -> Vec<Box<dyn Hittable + Sync + Send>> {
+            let mut objects: Vec<Box<dyn Hittable + Sync + Send>> = vec![
+                Box::new(Plane::new(
+                    Point3::new([0.0, -5.0, 0.0]),
+                    Vec3::new([0.0, 1.0, 0.0]),
+                    material.clone(),
+                )),
+                Box::new(Plane::new(
+                    Point3::new([8.0, 0.0, 0.0]),
+                    Vec3::new([-1.0, 0.0, 1.0]),
+                    material.clone(),
+                )),
+            ];
+            for &(center, radius) in &spheres {
+                objects.push(Box::new(Sphere::new(center, radius, material.clone())));
+            }
+            objects
+        };
+        let (list, bvh) = (List::from(build()), BvhNode::new(build()));
+        for _ in 0..2000 {
+            let ray = random_ray();
+            let ray_time = Interval::new(0.001, f64::INFINITY);
+            assert_eq!(list.hit(&ray, ray_time), bvh.hit(&ray, ray_time));
+        }
+    }
+
+    #[test]
+    fn empty_hierarchy_is_never_hit() {
+        let bvh = BvhNode::new(Vec::new());
+        assert!(bvh.hit(&random_ray(), Interval::UNIVERSE).is_none());
+    }
+}
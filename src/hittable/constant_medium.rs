@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable};
+use crate::{
+    aabb::Aabb, interval::Interval, material::Material, ray::Ray, rng::random, vec3::Vec3,
+};
+
+/// Volume of constant density filling a closed boundary, like fog or smoke
+pub struct ConstantMedium {
+    boundary: Box<dyn Hittable + Sync + Send>,
+    negative_inverse_density: f64,
+    phase_function: Arc<dyn Material + Sync + Send>,
+}
+
+impl ConstantMedium {
+    /// Fills the boundary, which must be closed, with a medium of the given density. The phase
+    /// function is the material scattering rays inside the volume, usually isotropic.
+    pub fn new(
+        boundary: Box<dyn Hittable + Sync + Send>,
+        density: f64,
+        phase_function: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            boundary,
+            negative_inverse_density: -density.recip(),
+            phase_function,
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        // Find where the line of the ray enters and leaves the boundary, so rays starting inside
+        // the volume also scatter
+        let entry = self.boundary.hit(ray, Interval::UNIVERSE)?;
+        let exit = self
+            .boundary
+            .hit(ray, Interval::UNIVERSE.with_min(entry.time() + 0.0001))?;
+
+        let entry_time = entry.time().max(ray_time.min()).max(0.0);
+        let exit_time = exit.time().min(ray_time.max());
+        if entry_time >= exit_time {
+            return None;
+        }
+
+        // Sample the free-flight distance from the exponential distribution
+        let ray_length = ray.direction().length();
+        let distance_inside_boundary = (exit_time - entry_time) * ray_length;
+        let hit_distance = self.negative_inverse_density * (1.0 - random::<f64>()).ln();
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let time = entry_time + hit_distance / ray_length;
+        // The normal and face are arbitrary, as isotropic scattering doesn't use them
+        Some(HitRecord::new(
+            ray.at(time),
+            Vec3::new([1.0, 0.0, 0.0]),
+            time,
+            ray,
+            self.phase_function.clone(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color, hittable::sphere::Sphere, material::isotropic::Isotropic, rng::reseed,
+        vec3::Point3,
+    };
+
+    const SAMPLES: usize = 20_000;
+
+    /// Medium of density 0.5 filling the unit sphere around the origin
+    fn medium() -> ConstantMedium {
+        let phase_function = Arc::new(Isotropic::new(Color::new([1.0; 3])));
+        ConstantMedium::new(
+            Box::new(Sphere::new(
+                Point3::new([0.0; 3]),
+                1.0,
+                phase_function.clone(),
+            )),
+            0.5,
+            phase_function,
+        )
+    }
+
+    /// Fraction of the rays going through the medium without scattering, checking the
+    /// scattering points lie along the ray inside the sphere
+    fn escape_fraction(medium: &ConstantMedium, ray: &Ray) -> f64 {
+        let escaped = (0..SAMPLES)
+            .filter(|_| {
+                let Some(record) = medium.hit(ray, Interval::new(0.001, f64::INFINITY)) else {
+                    return true;
+                };
+                assert!(record.time() > 0.001);
+                assert!(record.point().length() <= 1.0 + 1e-9);
+                false
+            })
+            .count();
+        escaped as f64 / SAMPLES as f64
+    }
+
+    #[test]
+    fn escape_fraction_matches_beer_lambert() {
+        reseed(56);
+        // Through the center, crossing a diameter of length 2
+        let ray = Ray::new(
+            Point3::new([-3.0, 0.0, 0.0]),
+            Vec3::new([0.5, 0.0, 0.0]),
+            0.0,
+        );
+        let expected = (-0.5_f64 * 2.0).exp();
+        let fraction = escape_fraction(&medium(), &ray);
+        assert!((fraction - expected).abs() < 0.02, "{fraction}");
+    }
+
+    #[test]
+    fn rays_starting_inside_scatter() {
+        reseed(57);
+        // From the center, crossing a radius of length 1
+        let ray = Ray::new(Point3::new([0.0; 3]), Vec3::new([0.0, 2.0, 0.0]), 0.0);
+        let expected = (-0.5_f64).exp();
+        let fraction = escape_fraction(&medium(), &ray);
+        assert!((fraction - expected).abs() < 0.02, "{fraction}");
+    }
+
+    #[test]
+    fn rays_missing_the_boundary_pass() {
+        let ray = Ray::new(
+            Point3::new([-3.0, 1.5, 0.0]),
+            Vec3::new([1.0, 0.0, 0.0]),
+            0.0,
+        );
+        assert!(medium().hit(&ray, Interval::UNIVERSE).is_none());
+    }
+}
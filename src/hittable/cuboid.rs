@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable, list::List, quad::Quad};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Axis-aligned box made of six quads, named to not shadow [`Box`]
+pub struct Cuboid {
+    sides: List,
+}
+
+impl Cuboid {
+    /// Creates the box with two opposite corners `a` and `b`
+    pub fn new(a: Point3, b: Point3, material: Arc<dyn Material + Sync + Send>) -> Self {
+        let min = Point3::new(std::array::from_fn(|axis| a[axis].min(b[axis])));
+        let max = Point3::new(std::array::from_fn(|axis| a[axis].max(b[axis])));
+
+        let dx = Vec3::new([max.x() - min.x(), 0.0, 0.0]);
+        let dy = Vec3::new([0.0, max.y() - min.y(), 0.0]);
+        let dz = Vec3::new([0.0, 0.0, max.z() - min.z()]);
+
+        let mut sides = List::default();
+        let mut add = |corner, u, v| sides.add(Box::new(Quad::new(corner, u, v, material.clone())));
+        // Front, right, back, left, top and bottom, all with outward normals
+        add(Point3::new([min.x(), min.y(), max.z()]), dx, dy);
+        add(Point3::new([max.x(), min.y(), max.z()]), -dz, dy);
+        add(Point3::new([max.x(), min.y(), min.z()]), -dx, dy);
+        add(min, dz, dy);
+        add(Point3::new([min.x(), max.y(), max.z()]), dx, -dz);
+        add(min, dx, dz);
+        Self { sides }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        self.sides.hit(ray, ray_time)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.sides.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        self.sides.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        self.sides.random(origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color, hittable::testing::assert_sampling_integrates_to_one,
+        material::lambertian::Lambertian, rng::reseed,
+    };
+
+    /// Box from (0, 0, 0) to (1, 2, 3), given by its corners in the wrong order
+    fn cuboid() -> Cuboid {
+        Cuboid::new(
+            Point3::new([1.0, 0.0, 3.0]),
+            Point3::new([0.0, 2.0, 0.0]),
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        )
+    }
+
+    #[test]
+    fn every_face_has_an_outward_normal() {
+        let cuboid = cuboid();
+        let center = Point3::new([0.5, 1.0, 1.5]);
+        for axis in 0..3 {
+            for side in [-1.0, 1.0] {
+                let mut outward = Vec3::default();
+                outward[axis] = side;
+                let ray = Ray::new(center + 10.0 * outward, -outward, 0.0);
+                let record = cuboid
+                    .hit(&ray, Interval::new(0.001, f64::INFINITY))
+                    .unwrap();
+                assert_eq!(*record.normal(), outward);
+                assert!(record.front_face(), "{outward:?}");
+
+                // From inside the normal still faces the ray, against the outward normal
+                let ray = Ray::new(center, outward, 0.0);
+                let record = cuboid
+                    .hit(&ray, Interval::new(0.001, f64::INFINITY))
+                    .unwrap();
+                assert_eq!(*record.normal(), -outward);
+                assert!(!record.front_face(), "{outward:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn density_integrates_to_one() {
+        reseed(53);
+        assert_sampling_integrates_to_one(&cuboid(), Point3::new([2.0, 3.0, 4.0]));
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    f64::consts::{PI, TAU},
+    sync::Arc,
+};
+
+use super::{
+    HitRecord, Hittable,
+    quad::{area_pdf_value, plane_hit_time},
+};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    ray::Ray,
+    rng::random,
+    vec3::{Point3, Vec3},
+};
+
+/// Flat circular disk
+pub struct Disk {
+    center: Point3,
+    radius: f64,
+
+    /// Unit normal, the side it points to is the front face
+    normal: Vec3,
+
+    /// Unit tangents spanning the disk plane, for texture coordinates
+    tangent: Vec3,
+    bitangent: Vec3,
+
+    /// Plane equation constant, `normal · point = d` for points in the plane
+    d: f64,
+
+    material: Arc<dyn Material + Sync + Send>,
+    bounding_box: Aabb,
+}
+
+impl Disk {
+    pub fn new(
+        center: Point3,
+        normal: Vec3,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        let normal = normal.unit_vector();
+        let radius = radius.max(0.0);
+        let helper = if normal.x().abs() > 0.9 {
+            Vec3::new([0.0, 1.0, 0.0])
+        } else {
+            Vec3::new([1.0, 0.0, 0.0])
+        };
+        let tangent = helper.cross(&normal).unit_vector();
+
+        // Extent of the disk along each axis is the radius times the sine of the angle between
+        // the axis and the normal
+        let extent = Vec3::new(std::array::from_fn(|axis| {
+            radius * (1.0 - normal[axis] * normal[axis]).max(0.0).sqrt()
+        }));
+        Self {
+            center,
+            radius,
+            normal,
+            tangent,
+            bitangent: normal.cross(&tangent),
+            d: normal.dot(&center),
+            material,
+            bounding_box: Aabb::from_points(center - extent, center + extent),
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let time = plane_hit_time(&self.normal, self.d, ray, ray_time)?;
+        let point = ray.at(time);
+        let offset = point - self.center;
+        let distance_squared = offset.length_squared();
+        if distance_squared > self.radius * self.radius {
+            return None;
+        }
+
+        // Polar texture coordinates: angle around the center and distance from it
+        let angle = offset.dot(&self.bitangent).atan2(offset.dot(&self.tangent));
+        let uv = [
+            angle.rem_euclid(TAU) / TAU,
+            distance_squared.sqrt() / self.radius,
+        ];
+        Some(HitRecord::new(point, self.normal, time, ray, self.material.clone()).with_uv(uv))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        area_pdf_value(
+            self,
+            PI * self.radius * self.radius,
+            &self.normal,
+            origin,
+            direction,
+        )
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        // The square root of the radius keeps points uniformly distributed over the area
+        let radius = self.radius * random::<f64>().sqrt();
+        let angle = TAU * random::<f64>();
+        self.center + radius * (angle.cos() * self.tangent + angle.sin() * self.bitangent) - *origin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color, hittable::testing::assert_sampling_integrates_to_one,
+        material::lambertian::Lambertian, rng::reseed,
+    };
+
+    /// Unit disk in the plane z = 0 around the origin
+    fn disk() -> Disk {
+        Disk::new(
+            Point3::new([0.0; 3]),
+            Vec3::new([0.0, 0.0, 1.0]),
+            1.0,
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        )
+    }
+
+    /// Ray straight down onto the plane z = 0 through the point, from z = 1
+    fn downward_ray(x: f64, y: f64) -> Ray {
+        Ray::new(Point3::new([x, y, 1.0]), Vec3::new([0.0, 0.0, -1.0]), 0.0)
+    }
+
+    const RAY_TIME: Interval = Interval::new(0.001, f64::INFINITY);
+
+    #[test]
+    fn hits_up_to_the_radius() {
+        let disk = disk();
+        assert!(disk.hit(&downward_ray(0.0, 0.0), RAY_TIME).is_some());
+        assert!(disk.hit(&downward_ray(0.999, 0.0), RAY_TIME).is_some());
+        assert!(disk.hit(&downward_ray(0.0, -1.0), RAY_TIME).is_some());
+        assert!(disk.hit(&downward_ray(1.001, 0.0), RAY_TIME).is_none());
+        assert!(disk.hit(&downward_ray(0.8, 0.8), RAY_TIME).is_none());
+    }
+
+    #[test]
+    fn density_integrates_to_one() {
+        reseed(52);
+        assert_sampling_integrates_to_one(&disk(), Point3::new([0.5, 0.0, 1.0]));
+    }
+}
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    ray::Ray,
+    rng::random,
+    vec3::{Point3, Vec3},
+    voxel_grid::VoxelGrid,
+};
+
+/// Volume with a density varying through space, given by a voxel grid stretched over a box
+pub struct HeterogeneousMedium {
+    grid: Arc<VoxelGrid>,
+    bounding_box: Aabb,
+
+    /// Multiplies the grid densities
+    density_scale: f64,
+
+    phase_function: Arc<dyn Material + Sync + Send>,
+}
+
+impl HeterogeneousMedium {
+    /// Stretches the grid over the box between two opposite corners, with densities scaled by
+    /// `density_scale`. The phase function is the material scattering rays inside the volume.
+    pub fn new(
+        grid: Arc<VoxelGrid>,
+        a: Point3,
+        b: Point3,
+        density_scale: f64,
+        phase_function: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self {
+            grid,
+            bounding_box: Aabb::from_points(a, b),
+            density_scale,
+            phase_function,
+        }
+    }
+
+    /// Returns the density at a point inside the box
+    fn density(&self, point: &Point3) -> f64 {
+        let position = std::array::from_fn(|axis| {
+            let interval = self.bounding_box[axis];
+            (point[axis] - interval.min()) / interval.size()
+        });
+        self.density_scale * self.grid.density(position)
+    }
+
+    /// Returns the majorant, an upper bound of the density everywhere in the volume
+    fn majorant(&self) -> f64 {
+        self.density_scale * self.grid.maximum()
+    }
+
+    /// Returns the times the ray enters and leaves the volume within the interval, if it does,
+    /// and the majorant per unit of ray time
+    fn clip(&self, ray: &Ray, ray_time: Interval) -> Option<(f64, f64, f64)> {
+        let inside = self.bounding_box.clip(ray, ray_time)?;
+        let ray_length = ray.direction().length();
+        let majorant = self.majorant();
+        (majorant > 0.0).then_some((inside.min(), inside.max(), majorant * ray_length))
+    }
+
+    /// Estimates the fraction of light passing along the ray through the volume within the
+    /// interval with ratio tracking, which is unbiased and never terminates early
+    pub fn transmittance(&self, ray: &Ray, ray_time: Interval) -> f64 {
+        let Some((mut time, exit_time, majorant)) = self.clip(ray, ray_time) else {
+            return 1.0;
+        };
+        let mut transmittance = 1.0;
+        loop {
+            time -= (1.0 - random::<f64>()).ln() / majorant;
+            if time >= exit_time {
+                return transmittance;
+            }
+            transmittance *= 1.0 - self.density(&ray.at(time)) / self.majorant();
+        }
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    /// Samples the next real collision with delta tracking: tentative collisions are sampled
+    /// against the majorant and accepted with the ratio of the actual density to it
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let (mut time, exit_time, majorant) = self.clip(ray, ray_time)?;
+        loop {
+            time -= (1.0 - random::<f64>()).ln() / majorant;
+            if time >= exit_time {
+                return None;
+            }
+            let point = ray.at(time);
+            if random::<f64>() * self.majorant() < self.density(&point) {
+                // The normal and face are arbitrary, as isotropic scattering doesn't use them
+                return Some(HitRecord::new(
+                    point,
+                    Vec3::new([1.0, 0.0, 0.0]),
+                    time,
+                    ray,
+                    self.phase_function.clone(),
+                ));
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+    use crate::{color::Color, material::isotropic::Isotropic, rng::reseed};
+
+    const SAMPLES: usize = 20_000;
+
+    /// Medium filling the box from (0, 0, 0) to (2, 2, 2)
+    fn medium(grid: VoxelGrid) -> HeterogeneousMedium {
+        HeterogeneousMedium::new(
+            Arc::new(grid),
+            Point3::new([0.0; 3]),
+            Point3::new([2.0; 3]),
+            1.0,
+            Arc::new(Isotropic::new(Color::new([1.0; 3]))),
+        )
+    }
+
+    /// Ray crossing the whole box along x, through a length of 2
+    fn crossing_ray() -> Ray {
+        Ray::new(
+            Point3::new([-1.0, 0.1, 0.1]),
+            Vec3::new([0.5, 0.0, 0.0]),
+            0.0,
+        )
+    }
+
+    fn mean_transmittance(medium: &HeterogeneousMedium) -> f64 {
+        (0..SAMPLES)
+            .map(|_| medium.transmittance(&crossing_ray(), Interval::new(0.001, f64::INFINITY)))
+            .sum::<f64>()
+            / SAMPLES as f64
+    }
+
+    fn escape_fraction(medium: &HeterogeneousMedium) -> f64 {
+        let escaped = (0..SAMPLES)
+            .filter(|_| {
+                medium
+                    .hit(&crossing_ray(), Interval::new(0.001, f64::INFINITY))
+                    .is_none()
+            })
+            .count();
+        escaped as f64 / SAMPLES as f64
+    }
+
+    #[test]
+    fn constant_grid_matches_beer_lambert() {
+        reseed(17);
+        let medium = medium(VoxelGrid::new([4, 4, 4], vec![0.5; 64]));
+        let expected = (-0.5_f64 * 2.0).exp();
+        assert!((mean_transmittance(&medium) - expected).abs() < 0.02);
+        assert!((escape_fraction(&medium) - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn sparse_background_below_majorant_matches_beer_lambert() {
+        // Background density 0.25, with a single dense leaf the ray never comes near raising
+        // the majorant to 1
+        let mut bytes = b"SPVX".to_vec();
+        for value in [16_u32, 16, 16] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(0.25_f32.to_le_bytes());
+        bytes.extend(1_u32.to_le_bytes());
+        for value in [1_u32, 1, 1] {
+            bytes.extend(value.to_le_bytes());
+        }
+        for _ in 0..512 {
+            bytes.extend(1.0_f32.to_le_bytes());
+        }
+        let path: PathBuf = std::env::temp_dir().join(format!(
+            "heterogeneous_medium_test_{}.spvx",
+            std::process::id()
+        ));
+        fs::write(&path, bytes).unwrap();
+        let grid = VoxelGrid::load_sparse(&path);
+        fs::remove_file(&path).unwrap();
+        let grid = grid.unwrap();
+        assert_eq!(grid.maximum(), 1.0);
+
+        reseed(29);
+        let medium = medium(grid);
+        let expected = (-0.25_f64 * 2.0).exp();
+        assert!((mean_transmittance(&medium) - expected).abs() < 0.02);
+        assert!((escape_fraction(&medium) - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn empty_grid_is_transparent() {
+        let medium = medium(VoxelGrid::new([2, 2, 2], vec![0.0; 8]));
+        let ray = crossing_ray();
+        assert_eq!(medium.transmittance(&ray, Interval::UNIVERSE), 1.0);
+        assert!(medium.hit(&ray, Interval::UNIVERSE).is_none());
+    }
+}
@@ -1,31 +1,59 @@
-use crate::{interval::Interval, ray::Ray};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    ray::Ray,
+    rng::random_range,
+    vec3::{Point3, Vec3},
+};
 
 use super::Hittable;
 
 #[derive(Default)]
 pub struct List {
-    objects: Vec<Box<dyn Hittable + Sync>>,
+    objects: Vec<Box<dyn Hittable + Sync + Send>>,
+    bounding_box: Aabb,
 }
 
 impl List {
-    pub fn new(object: Box<dyn Hittable + Sync>) -> Self {
-        Self {
-            objects: vec![object],
-        }
+    pub fn new(object: Box<dyn Hittable + Sync + Send>) -> Self {
+        let mut list = Self::default();
+        list.add(object);
+        list
     }
 
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.bounding_box = Aabb::EMPTY;
     }
 
-    pub fn add(&mut self, object: Box<dyn Hittable + Sync>) {
+    pub fn add(&mut self, object: Box<dyn Hittable + Sync + Send>) {
+        self.bounding_box = Aabb::surrounding(&self.bounding_box, &object.bounding_box());
         self.objects.push(object);
     }
+
+    pub const fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Consumes the list, returning the objects it contained
+    pub fn into_objects(self) -> Vec<Box<dyn Hittable + Sync + Send>> {
+        self.objects
+    }
 }
 
-impl From<Vec<Box<dyn Hittable + Sync>>> for List {
-    fn from(value: Vec<Box<dyn Hittable + Sync>>) -> Self {
-        Self { objects: value }
+impl From<Vec<Box<dyn Hittable + Sync + Send>>> for List {
+    fn from(value: Vec<Box<dyn Hittable + Sync + Send>>) -> Self {
+        let bounding_box = value.iter().fold(Aabb::EMPTY, |bounding_box, object| {
+            Aabb::surrounding(&bounding_box, &object.bounding_box())
+        });
+        Self {
+            objects: value,
+            bounding_box,
+        }
     }
 }
 
@@ -40,4 +68,27 @@ impl Hittable for List {
         }
         record
     }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    /// Samples every object with the same probability
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects
+            .iter()
+            .map(|object| weight * object.pdf_value(origin, direction))
+            .sum()
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new([1.0, 0.0, 0.0]);
+        }
+        self.objects[random_range(0..self.objects.len())].random(origin)
+    }
 }
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable, bvh::BvhNode, triangle};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Vertex attribute buffers, shared by every triangle of one or more meshes
+#[derive(Debug, Default)]
+pub struct MeshBuffers {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<[f64; 2]>,
+}
+
+/// Indices of the vertex attributes of a single triangle into [`MeshBuffers`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Face {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+}
+
+impl Face {
+    pub const fn new(positions: [usize; 3]) -> Self {
+        Self {
+            positions,
+            normals: None,
+            uvs: None,
+        }
+    }
+
+    /// Returns true if every index is within the given buffers
+    fn is_within(&self, buffers: &MeshBuffers) -> bool {
+        self.positions
+            .iter()
+            .all(|&index| index < buffers.positions.len())
+            && self
+                .normals
+                .is_none_or(|normals| normals.iter().all(|&index| index < buffers.normals.len()))
+            && self
+                .uvs
+                .is_none_or(|uvs| uvs.iter().all(|&index| index < buffers.uvs.len()))
+    }
+}
+
+/// A single face of a mesh, reading its vertices from the shared buffers
+struct MeshTriangle {
+    buffers: Arc<MeshBuffers>,
+    face: Face,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl MeshTriangle {
+    fn vertices(&self) -> [Point3; 3] {
+        self.face
+            .positions
+            .map(|index| self.buffers.positions[index])
+    }
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let vertices = self.vertices();
+        let (time, barycentric) = triangle::intersect(&vertices, ray, ray_time)?;
+        let normals = self
+            .face
+            .normals
+            .map(|indices| indices.map(|index| self.buffers.normals[index]));
+        let uvs = self
+            .face
+            .uvs
+            .map(|indices| indices.map(|index| self.buffers.uvs[index]));
+        Some(triangle::hit_record(
+            &vertices,
+            normals.as_ref(),
+            uvs.as_ref(),
+            ray,
+            time,
+            barycentric,
+            self.material.clone(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        triangle::bounding_box(&self.vertices())
+    }
+}
+
+/// Triangle mesh sharing one set of vertex buffers between all of its faces
+pub struct Mesh {
+    triangles: BvhNode,
+}
+
+impl Mesh {
+    /// Creates a mesh from shared buffers and faces indexing into them.
+    ///
+    /// # Panics
+    /// Panics if a face refers to a vertex attribute outside of the buffers.
+    pub fn new(
+        buffers: Arc<MeshBuffers>,
+        faces: impl IntoIterator<Item = Face>,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        let triangles = faces
+            .into_iter()
+            .map(|face| -> Box<dyn Hittable + Sync + Send> {
+                assert!(
+                    face.is_within(&buffers),
+                    "mesh face {face:?} is out of bounds"
+                );
+                Box::new(MeshTriangle {
+                    buffers: buffers.clone(),
+                    face,
+                    material: material.clone(),
+                })
+            })
+            .collect();
+        Self {
+            triangles: BvhNode::new(triangles),
+        }
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        self.triangles.hit(ray, ray_time)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.triangles.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        hittable::{list::List, triangle::Triangle},
+        material::lambertian::Lambertian,
+        rng::{random_range, reseed},
+    };
+
+    #[test]
+    fn faces_match_standalone_triangles() {
+        reseed(41);
+        let material: Arc<dyn Material + Sync + Send> =
+            Arc::new(Lambertian::new(Color::new([0.5; 3])));
+        let buffers = Arc::new(MeshBuffers {
+            positions: vec![
+                Point3::new([0.0, 0.0, 0.0]),
+                Point3::new([1.0, 0.0, 0.2]),
+                Point3::new([1.0, 1.0, 0.0]),
+                Point3::new([0.0, 1.0, -0.3]),
+            ],
+            normals: vec![
+                Vec3::new([0.0, 0.0, 1.0]),
+                Vec3::new([0.3, 0.0, 1.0]),
+                Vec3::new([0.0, 0.3, 1.0]),
+            ],
+            uvs: vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+        });
+        let faces = [
+            Face {
+                positions: [0, 1, 2],
+                normals: Some([0, 1, 2]),
+                uvs: Some([0, 1, 2]),
+            },
+            Face {
+                positions: [0, 2, 3],
+                normals: None,
+                uvs: Some([0, 2, 3]),
+            },
+        ];
+        let mesh = Mesh::new(buffers.clone(), faces, material.clone());
+
+        let mut triangles = List::default();
+        for face in faces {
+            let mut triangle = Triangle::new(
+                face.positions.map(|index| buffers.positions[index]),
+                material.clone(),
+            );
+            if let Some(normals) = face.normals {
+                triangle = triangle.with_normals(normals.map(|index| buffers.normals[index]));
+            }
+            if let Some(uvs) = face.uvs {
+                triangle = triangle.with_uvs(uvs.map(|index| buffers.uvs[index]));
+            }
+            triangles.add(Box::new(triangle));
+        }
+
+        assert_eq!(mesh.bounding_box(), triangles.bounding_box());
+        let mut hits = 0;
+        for _ in 0..2000 {
+            let origin = Point3::new([
+                random_range(-0.5..1.5),
+                random_range(-0.5..1.5),
+                random_range(1.0..3.0),
+            ]);
+            let target = Point3::new([random_range(-0.2..1.2), random_range(-0.2..1.2), 0.0]);
+            let ray = Ray::new(origin, target - origin, 0.0);
+            let ray_time = Interval::new(0.001, f64::INFINITY);
+            let record = mesh.hit(&ray, ray_time);
+            hits += usize::from(record.is_some());
+            assert_eq!(record, triangles.hit(&ray, ray_time));
+        }
+        assert!(hits > 500, "{hits}");
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn faces_outside_the_buffers_are_rejected() {
+        let buffers = Arc::new(MeshBuffers {
+            positions: vec![Point3::default(); 3],
+            ..MeshBuffers::default()
+        });
+        Mesh::new(
+            buffers,
+            [Face::new([0, 1, 3])],
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        );
+    }
+}
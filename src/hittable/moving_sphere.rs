@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable, sphere::Sphere};
+use crate::{aabb::Aabb, interval::Interval, material::Material, ray::Ray, vec3::Point3};
+
+/// Sphere whose center moves along a path during the shutter interval, for motion blur
+pub struct MovingSphere {
+    /// Center positions at increasing shutter times, linearly interpolated in between and held
+    /// before the first and after the last
+    keyframes: Vec<(f64, Point3)>,
+    radius: f64,
+    material: Arc<dyn Material + Sync + Send>,
+    bounding_box: Aabb,
+}
+
+impl MovingSphere {
+    /// Creates a sphere moving from `center0` at shutter time 0 to `center1` at time 1
+    pub fn new(
+        center0: Point3,
+        center1: Point3,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        Self::with_keyframes(vec![(0.0, center0), (1.0, center1)], radius, material)
+    }
+
+    /// Creates a sphere following a path of `(shutter time, center)` keyframes.
+    ///
+    /// # Panics
+    /// Panics if there are no keyframes.
+    pub fn with_keyframes(
+        mut keyframes: Vec<(f64, Point3)>,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        assert!(!keyframes.is_empty(), "a moving sphere needs a keyframe");
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let radius = radius.max(0.0);
+
+        // The path between keyframes is straight, so the boxes at the keyframes enclose it
+        let bounding_box = keyframes
+            .iter()
+            .fold(Aabb::EMPTY, |bounding_box, (_, center)| {
+                Aabb::surrounding(&bounding_box, &Sphere::bounding_box_at(*center, radius))
+            });
+        Self {
+            keyframes,
+            radius,
+            material,
+            bounding_box,
+        }
+    }
+
+    /// Returns the center of the sphere at the given shutter time
+    fn center(&self, shutter_time: f64) -> Point3 {
+        let next = self
+            .keyframes
+            .partition_point(|(time, _)| *time <= shutter_time);
+        match next {
+            0 => self.keyframes[0].1,
+            next if next == self.keyframes.len() => self.keyframes[next - 1].1,
+            next => {
+                let (time0, center0) = self.keyframes[next - 1];
+                let (time1, center1) = self.keyframes[next];
+                let t = (shutter_time - time0) / (time1 - time0);
+                center0 + t * (center1 - center0)
+            }
+        }
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let center = self.center(ray.shutter_time());
+        Sphere::hit_at(center, self.radius, &self.material, ray, ray_time)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, material::lambertian::Lambertian, vec3::Vec3};
+
+    /// Sphere of radius 0.5 moving along x then y, with keyframes given out of order
+    fn sphere() -> MovingSphere {
+        MovingSphere::with_keyframes(
+            vec![
+                (1.0, Point3::new([2.0, 0.0, 0.0])),
+                (2.0, Point3::new([2.0, 2.0, 0.0])),
+                (0.0, Point3::new([0.0, 0.0, 0.0])),
+            ],
+            0.5,
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        )
+    }
+
+    #[test]
+    fn keyframes_are_sorted_by_time() {
+        let times: Vec<_> = sphere().keyframes.iter().map(|(time, _)| *time).collect();
+        assert_eq!(times, [0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn center_interpolates_between_keyframes() {
+        let sphere = sphere();
+        assert_eq!(sphere.center(0.0), Point3::new([0.0, 0.0, 0.0]));
+        assert_eq!(sphere.center(0.5), Point3::new([1.0, 0.0, 0.0]));
+        assert_eq!(sphere.center(1.0), Point3::new([2.0, 0.0, 0.0]));
+        assert_eq!(sphere.center(1.25), Point3::new([2.0, 0.5, 0.0]));
+        assert_eq!(sphere.center(2.0), Point3::new([2.0, 2.0, 0.0]));
+    }
+
+    #[test]
+    fn center_is_held_outside_the_keyframes() {
+        let sphere = sphere();
+        assert_eq!(sphere.center(-3.0), Point3::new([0.0, 0.0, 0.0]));
+        assert_eq!(sphere.center(5.0), Point3::new([2.0, 2.0, 0.0]));
+
+        let still = MovingSphere::with_keyframes(
+            vec![(0.5, Point3::new([1.0, 2.0, 3.0]))],
+            1.0,
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        );
+        for time in [0.0, 0.5, 1.0] {
+            assert_eq!(still.center(time), Point3::new([1.0, 2.0, 3.0]));
+        }
+    }
+
+    #[test]
+    fn bounding_box_encloses_every_keyframe() {
+        assert_eq!(
+            sphere().bounding_box(),
+            Aabb::from_points(
+                Point3::new([-0.5, -0.5, -0.5]),
+                Point3::new([2.5, 2.5, 0.5])
+            )
+        );
+    }
+
+    #[test]
+    fn hits_follow_the_shutter_time() {
+        let sphere = sphere();
+        // Rays along z at shutter time, through the center there and through where the sphere
+        // is at the other times
+        let cases = [
+            (-1.0, [0.0, 0.0]),
+            (0.5, [1.0, 0.0]),
+            (1.5, [2.0, 1.0]),
+            (3.0, [2.0, 2.0]),
+        ];
+        for (shutter_time, [x, y]) in cases {
+            for (_, [other_x, other_y]) in cases {
+                let ray = Ray::new(
+                    Point3::new([other_x, other_y, -5.0]),
+                    Vec3::new([0.0, 0.0, 1.0]),
+                    shutter_time,
+                );
+                let record = sphere.hit(&ray, Interval::new(0.001, f64::INFINITY));
+                if (other_x, other_y) == (x, y) {
+                    let record = record.unwrap();
+                    assert_eq!(*record.point(), Point3::new([x, y, -0.5]));
+                    assert_eq!(record.time(), 4.5);
+                } else {
+                    assert!(record.is_none(), "{shutter_time} {other_x} {other_y}");
+                }
+            }
+        }
+    }
+}
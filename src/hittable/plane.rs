@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable, quad::plane_hit_time};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Infinite plane through a point
+pub struct Plane {
+    point: Point3,
+
+    /// Unit normal, the side it points to is the front face
+    normal: Vec3,
+
+    /// Unit tangents spanning the plane, for texture coordinates
+    tangent: Vec3,
+    bitangent: Vec3,
+
+    /// Plane equation constant, `normal · point = d` for points in the plane
+    d: f64,
+
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: Arc<dyn Material + Sync + Send>) -> Self {
+        let normal = normal.unit_vector();
+        let helper = if normal.x().abs() > 0.9 {
+            Vec3::new([0.0, 1.0, 0.0])
+        } else {
+            Vec3::new([1.0, 0.0, 0.0])
+        };
+        let tangent = helper.cross(&normal).unit_vector();
+        Self {
+            point,
+            normal,
+            tangent,
+            bitangent: normal.cross(&tangent),
+            d: normal.dot(&point),
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let time = plane_hit_time(&self.normal, self.d, ray, ray_time)?;
+        let point = ray.at(time);
+
+        // Tile the texture once per unit of distance along the plane
+        let planar_hit = point - self.point;
+        let uv = [
+            planar_hit.dot(&self.tangent).rem_euclid(1.0),
+            planar_hit.dot(&self.bitangent).rem_euclid(1.0),
+        ];
+        Some(HitRecord::new(point, self.normal, time, ray, self.material.clone()).with_uv(uv))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // Only bounded along an axis aligned normal
+        let axis_extent = |axis: usize| {
+            if (self.normal[axis].abs() - 1.0).abs() < 1e-12 {
+                Interval::new(self.point[axis], self.point[axis])
+            } else {
+                Interval::UNIVERSE
+            }
+        };
+        Aabb::new(axis_extent(0), axis_extent(1), axis_extent(2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, material::lambertian::Lambertian};
+
+    /// The plane y = 0
+    fn ground() -> Plane {
+        Plane::new(
+            Point3::new([0.0; 3]),
+            Vec3::new([0.0, 2.0, 0.0]),
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        )
+    }
+
+    #[test]
+    fn hits_within_the_interval() {
+        let ray = Ray::new(
+            Point3::new([3.0, 1.0, -2.0]),
+            Vec3::new([0.0, -1.0, 0.0]),
+            0.0,
+        );
+        let record = ground()
+            .hit(&ray, Interval::new(0.001, f64::INFINITY))
+            .unwrap();
+        assert_eq!(record.time(), 1.0);
+        assert_eq!(*record.point(), Point3::new([3.0, 0.0, -2.0]));
+        assert_eq!(*record.normal(), Vec3::new([0.0, 1.0, 0.0]));
+        assert!(ground().hit(&ray, Interval::new(0.001, 0.5)).is_none());
+        // The interval bounds are excluded
+        assert!(ground().hit(&ray, Interval::new(0.001, 1.0)).is_none());
+        assert!(ground().hit(&ray, Interval::new(1.0, 2.0)).is_none());
+    }
+
+    #[test]
+    fn parallel_rays_miss() {
+        let ray = Ray::new(
+            Point3::new([0.0, 1.0, 0.0]),
+            Vec3::new([1.0, 0.0, 1.0]),
+            0.0,
+        );
+        assert!(
+            ground()
+                .hit(&ray, Interval::new(0.001, f64::INFINITY))
+                .is_none()
+        );
+        let inside = Ray::new(Point3::new([0.0; 3]), Vec3::new([1.0, 0.0, 0.0]), 0.0);
+        assert!(ground().hit(&inside, Interval::UNIVERSE).is_none());
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    ray::Ray,
+    rng::random,
+    vec3::{Point3, Vec3},
+};
+
+/// Parallelogram spanned by two edge vectors from a corner
+pub struct Quad {
+    corner: Point3,
+    u: Vec3,
+    v: Vec3,
+
+    /// Cached `n / (n · n)` with `n = u × v`, to find the planar coordinates of a hit point
+    w: Vec3,
+
+    /// Unit normal of the plane containing the quad
+    normal: Vec3,
+
+    /// Plane equation constant, `normal · point = d` for points in the plane
+    d: f64,
+
+    area: f64,
+    material: Arc<dyn Material + Sync + Send>,
+    bounding_box: Aabb,
+}
+
+impl Quad {
+    pub fn new(
+        corner: Point3,
+        u: Vec3,
+        v: Vec3,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        let n = u.cross(&v);
+        let normal = n.unit_vector();
+        let bounding_box = Aabb::surrounding(
+            &Aabb::from_points(corner, corner + u + v),
+            &Aabb::from_points(corner + u, corner + v),
+        );
+        Self {
+            corner,
+            u,
+            v,
+            w: n / n.dot(&n),
+            normal,
+            d: normal.dot(&corner),
+            area: n.length(),
+            material,
+            bounding_box,
+        }
+    }
+
+    pub const fn corner(&self) -> &Point3 {
+        &self.corner
+    }
+
+    pub const fn u(&self) -> &Vec3 {
+        &self.u
+    }
+
+    pub const fn v(&self) -> &Vec3 {
+        &self.v
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let time = plane_hit_time(&self.normal, self.d, ray, ray_time)?;
+
+        // Determine if the hit point lies within the quad using its planar coordinates
+        let point = ray.at(time);
+        let planar_hit = point - self.corner;
+        let alpha = self.w.dot(&planar_hit.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar_hit));
+        let unit_interval = Interval::new(0.0, 1.0);
+        if !unit_interval.contains(alpha) || !unit_interval.contains(beta) {
+            return None;
+        }
+
+        Some(
+            HitRecord::new(point, self.normal, time, ray, self.material.clone())
+                .with_uv([alpha, beta]),
+        )
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        area_pdf_value(self, self.area, &self.normal, origin, direction)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        self.corner + random::<f64>() * self.u + random::<f64>() * self.v - *origin
+    }
+}
+
+/// Returns the density per unit solid angle of sampling the direction by picking a uniformly
+/// distributed point on a flat surface of the given area. The normal must be the unit normal
+/// of the surface itself, not a shading normal, as it gives the solid angle a patch covers.
+pub(super) fn area_pdf_value(
+    surface: &dyn Hittable,
+    area: f64,
+    normal: &Vec3,
+    origin: &Point3,
+    direction: &Vec3,
+) -> f64 {
+    let Some(record) = surface.hit(
+        &Ray::new(*origin, *direction, 0.0),
+        Interval::new(0.001, f64::INFINITY),
+    ) else {
+        return 0.0;
+    };
+    let distance_squared = record.time() * record.time() * direction.length_squared();
+    let cosine = direction.dot(normal).abs() / direction.length();
+    distance_squared / (cosine * area)
+}
+
+/// Returns the ray time where the ray crosses the plane `normal · point = d`, if the ray is not
+/// parallel to the plane and the time is within `ray_time`
+pub(super) fn plane_hit_time(normal: &Vec3, d: f64, ray: &Ray, ray_time: Interval) -> Option<f64> {
+    let denominator = normal.dot(ray.direction());
+    if denominator.abs() < 1e-8 {
+        return None;
+    }
+    let time = (d - normal.dot(ray.origin())) / denominator;
+    ray_time.surrounds(time).then_some(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color, hittable::testing::assert_sampling_integrates_to_one,
+        material::lambertian::Lambertian, rng::reseed,
+    };
+
+    /// Quad in the plane z = 0 spanned by (2, 0, 0) and (0, 1, 0) from the origin
+    fn quad() -> Quad {
+        Quad::new(
+            Point3::new([0.0; 3]),
+            Vec3::new([2.0, 0.0, 0.0]),
+            Vec3::new([0.0, 1.0, 0.0]),
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        )
+    }
+
+    /// Ray straight down onto the plane z = 0 through the point, from z = 1
+    fn downward_ray(x: f64, y: f64) -> Ray {
+        Ray::new(Point3::new([x, y, 1.0]), Vec3::new([0.0, 0.0, -1.0]), 0.0)
+    }
+
+    const RAY_TIME: Interval = Interval::new(0.001, f64::INFINITY);
+
+    #[test]
+    fn hits_inside_and_misses_outside() {
+        let quad = quad();
+        let record = quad.hit(&downward_ray(1.5, 0.25), RAY_TIME).unwrap();
+        assert_eq!(*record.point(), Point3::new([1.5, 0.25, 0.0]));
+        assert_eq!(*record.normal(), Vec3::new([0.0, 0.0, 1.0]));
+        assert!(record.front_face());
+        assert!(quad.hit(&downward_ray(2.5, 0.5), RAY_TIME).is_none());
+        assert!(quad.hit(&downward_ray(1.0, -0.1), RAY_TIME).is_none());
+        assert!(quad.hit(&downward_ray(1.0, 1.1), RAY_TIME).is_none());
+    }
+
+    #[test]
+    fn uvs_are_the_planar_coordinates() {
+        let quad = quad();
+        let record = quad.hit(&downward_ray(1.5, 0.25), RAY_TIME).unwrap();
+        assert_eq!(record.uv(), [0.75, 0.25]);
+        let corner = quad.hit(&downward_ray(2.0, 1.0), RAY_TIME).unwrap();
+        assert_eq!(corner.uv(), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn density_integrates_to_one() {
+        reseed(51);
+        assert_sampling_integrates_to_one(&quad(), Point3::new([1.0, 0.5, 1.0]));
+    }
+}
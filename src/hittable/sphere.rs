@@ -1,34 +1,51 @@
-use std::sync::Arc;
+use std::{f64::consts::PI, sync::Arc};
 
 use super::{HitRecord, Hittable};
-use crate::{interval::Interval, material::Material, ray::Ray, vec3::Point3};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    onb::Onb,
+    ray::Ray,
+    rng::random,
+    vec3::{Point3, Vec3},
+};
 
 pub struct Sphere {
     center: Point3,
     radius: f64,
     material: Arc<dyn Material + Sync + Send>,
+    bounding_box: Aabb,
 }
 
 impl Sphere {
-    pub const fn new(
-        center: Point3,
-        radius: f64,
-        material: Arc<dyn Material + Sync + Send>,
-    ) -> Self {
+    pub fn new(center: Point3, radius: f64, material: Arc<dyn Material + Sync + Send>) -> Self {
+        let radius = radius.max(0.0);
         Self {
             center,
-            radius: radius.max(0.0),
+            radius,
             material,
+            bounding_box: Self::bounding_box_at(center, radius),
         }
     }
-}
 
-impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
-        let origin_center = self.center - *ray.origin();
+    pub(super) fn bounding_box_at(center: Point3, radius: f64) -> Aabb {
+        let radius_vector = Vec3::new([radius; 3]);
+        Aabb::from_points(center - radius_vector, center + radius_vector)
+    }
+
+    /// Intersects the ray with a sphere, shared with spheres whose center moves
+    pub(super) fn hit_at(
+        center: Point3,
+        radius: f64,
+        material: &Arc<dyn Material + Sync + Send>,
+        ray: &Ray,
+        ray_time: Interval,
+    ) -> Option<HitRecord> {
+        let origin_center = center - *ray.origin();
         let a = ray.direction().length_squared();
         let h = ray.direction().dot(&origin_center);
-        let c = origin_center.length_squared() - self.radius * self.radius;
+        let c = origin_center.length_squared() - radius * radius;
         let discriminant = h * h - a * c;
         if discriminant < 0.0 {
             return None;
@@ -46,12 +63,91 @@ impl Hittable for Sphere {
         }
 
         let point = ray.at(root);
-        Some(HitRecord::new(
-            point,
-            (point - self.center) / self.radius,
-            root,
-            ray,
-            self.material.clone(),
-        ))
+        let outward_normal = (point - center) / radius;
+        Some(
+            HitRecord::new(point, outward_normal, root, ray, material.clone())
+                .with_uv(Self::uv(&outward_normal)),
+        )
+    }
+
+    /// Returns the texture coordinates of a point on the unit sphere, with `u` going around
+    /// the y axis starting from -x, and `v` going from -y up to +y.
+    fn uv(point: &Point3) -> [f64; 2] {
+        let theta = (-point.y()).acos();
+        let phi = (-point.z()).atan2(point.x()) + PI;
+        [phi / (2.0 * PI), theta / PI]
+    }
+}
+
+impl Hittable for Sphere {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        Self::hit_at(self.center, self.radius, &self.material, ray, ray_time)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    /// Samples the cone of directions toward the sphere, or every direction from inside it
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        let distance_squared = (self.center - *origin).length_squared();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
+            return 1.0 / (4.0 * PI);
+        }
+        if self
+            .hit(
+                &Ray::new(*origin, *direction, 0.0),
+                Interval::new(0.001, f64::INFINITY),
+            )
+            .is_none()
+        {
+            return 0.0;
+        }
+        let cos_theta_max = (1.0 - radius_squared / distance_squared).sqrt();
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        let direction = self.center - *origin;
+        let distance_squared = direction.length_squared();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
+            return Vec3::random_unit_vector();
+        }
+
+        // Uniformly distributed direction within the cone around `direction`
+        let r1 = random::<f64>();
+        let r2 = random::<f64>();
+        let cos_theta_max = (1.0 - radius_squared / distance_squared).sqrt();
+        let z = 1.0 + r2 * (cos_theta_max - 1.0);
+        let phi = 2.0 * PI * r1;
+        let sin_theta = (1.0 - z * z).sqrt();
+        Onb::new(&direction).transform(&Vec3::new([
+            phi.cos() * sin_theta,
+            phi.sin() * sin_theta,
+            z,
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color, hittable::testing::assert_sampling_integrates_to_one,
+        material::lambertian::Lambertian, rng::reseed,
+    };
+
+    #[test]
+    fn density_integrates_to_one() {
+        reseed(54);
+        let sphere = Sphere::new(
+            Point3::new([0.0, 1.0, 0.0]),
+            1.0,
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        );
+        assert_sampling_integrates_to_one(&sphere, Point3::new([0.0, 1.0, -3.0]));
     }
 }
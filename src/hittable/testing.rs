@@ -0,0 +1,36 @@
+//! Checks shared by the tests of the hittables
+
+use std::f64::consts::PI;
+
+use crate::{
+    hittable::Hittable,
+    interval::Interval,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Asserts that directions sampled toward the object from the origin hit it with a positive
+/// density, and that the density integrates to one over all directions
+pub fn assert_sampling_integrates_to_one(object: &dyn Hittable, origin: Point3) {
+    for _ in 0..1000 {
+        let direction = object.random(&origin);
+        let ray = Ray::new(origin, direction, 0.0);
+        assert!(
+            object
+                .hit(&ray, Interval::new(0.001, f64::INFINITY))
+                .is_some(),
+            "{direction:?} misses the object"
+        );
+        assert!(object.pdf_value(&origin, &direction) > 0.0);
+    }
+
+    // Uniform directions over the sphere have density 1 / 4π
+    let samples = 200_000;
+    let integral = (0..samples)
+        .map(|_| object.pdf_value(&origin, &Vec3::random_unit_vector()))
+        .sum::<f64>()
+        * 4.0
+        * PI
+        / samples as f64;
+    assert!((integral - 1.0).abs() < 0.03, "{integral}");
+}
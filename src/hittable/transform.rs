@@ -0,0 +1,267 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    matrix::Matrix4,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Affine transform with the inverses needed to move rays into object space and hits back out
+#[derive(Debug, Clone, Copy)]
+struct ObjectToWorld {
+    matrix: Matrix4,
+
+    /// Moves rays from world space into object space
+    inverse: Matrix4,
+
+    /// Inverse transpose, which keeps normals perpendicular to surfaces under non-uniform scaling
+    normal_matrix: Matrix4,
+
+    /// Factor by which moving into object space scales volumes
+    inverse_determinant: f64,
+}
+
+impl ObjectToWorld {
+    fn new(matrix: Matrix4) -> Option<Self> {
+        let inverse = matrix.inverse()?;
+        Some(Self {
+            matrix,
+            inverse,
+            normal_matrix: inverse.transpose(),
+            inverse_determinant: inverse.linear_determinant(),
+        })
+    }
+
+    /// Hits the object with the ray moved into object space. The ray direction is not
+    /// normalized, so the ray time of a hit is the same in both spaces.
+    fn hit(&self, object: &dyn Hittable, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let object_ray = Ray::new(
+            self.inverse.transform_point(ray.origin()),
+            self.inverse.transform_vector(ray.direction()),
+            ray.shutter_time(),
+        );
+        let record = object.hit(&object_ray, ray_time)?;
+        let point = self.matrix.transform_point(record.point());
+        let normal = self
+            .normal_matrix
+            .transform_vector(record.normal())
+            .unit_vector();
+        Some(record.transformed(point, normal))
+    }
+
+    /// Returns the density of sampling the direction toward the object. Moving a direction
+    /// `ω` of unit length into object space scales solid angles by `|det L⁻¹| / |L⁻¹ ω|³`,
+    /// where `L` is the linear part of the transform.
+    fn pdf_value(&self, object: &dyn Hittable, origin: &Point3, direction: &Vec3) -> f64 {
+        let object_direction = self.inverse.transform_vector(direction);
+        let density = object.pdf_value(&self.inverse.transform_point(origin), &object_direction);
+        if density == 0.0 {
+            return 0.0;
+        }
+        let stretch = object_direction.length() / direction.length();
+        density * self.inverse_determinant.abs() / stretch.powi(3)
+    }
+
+    /// Samples a direction toward the object in object space and moves it back out
+    fn random(&self, object: &dyn Hittable, origin: &Point3) -> Vec3 {
+        let direction = object.random(&self.inverse.transform_point(origin));
+        self.matrix.transform_vector(&direction)
+    }
+
+    /// Returns the box around the transformed corners of the object box
+    fn bounding_box(&self, object_box: &Aabb) -> Aabb {
+        let (min, max) = (object_box.min(), object_box.max());
+        let corners = (0..8).map(|corner| {
+            Point3::new(std::array::from_fn(|axis| {
+                if corner >> axis & 1 == 0 {
+                    min[axis]
+                } else {
+                    max[axis]
+                }
+            }))
+        });
+        let mut bounding_box = Aabb::EMPTY;
+        for corner in corners {
+            let corner = self.matrix.transform_point(&corner);
+            if !(0..3).all(|axis| corner[axis].is_finite()) {
+                // Unbounded objects stay unbounded
+                return Aabb::new(Interval::UNIVERSE, Interval::UNIVERSE, Interval::UNIVERSE);
+            }
+            bounding_box = Aabb::surrounding(&bounding_box, &Aabb::from_points(corner, corner));
+        }
+        bounding_box
+    }
+}
+
+/// Hittable moved, rotated and scaled by an affine transform
+pub struct Transform {
+    object: Box<dyn Hittable + Sync + Send>,
+    object_to_world: ObjectToWorld,
+    bounding_box: Aabb,
+}
+
+impl Transform {
+    /// Wraps the object in the transform, or returns `None` if the matrix can't be inverted
+    pub fn new(object: Box<dyn Hittable + Sync + Send>, matrix: Matrix4) -> Option<Self> {
+        let object_to_world = ObjectToWorld::new(matrix)?;
+        Some(Self {
+            bounding_box: object_to_world.bounding_box(&object.bounding_box()),
+            object,
+            object_to_world,
+        })
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        self.object_to_world
+            .hit(self.object.as_ref(), ray, ray_time)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        self.object_to_world
+            .pdf_value(self.object.as_ref(), origin, direction)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        self.object_to_world.random(self.object.as_ref(), origin)
+    }
+}
+
+/// Placement of shared geometry, so one object can appear many times with its own transform
+/// and optionally its own material
+pub struct Instance {
+    geometry: Arc<dyn Hittable + Sync + Send>,
+    object_to_world: ObjectToWorld,
+    material: Option<Arc<dyn Material + Sync + Send>>,
+    bounding_box: Aabb,
+}
+
+impl Instance {
+    /// Places the geometry with the transform, or returns `None` if the matrix can't be
+    /// inverted
+    pub fn new(geometry: Arc<dyn Hittable + Sync + Send>, matrix: Matrix4) -> Option<Self> {
+        let object_to_world = ObjectToWorld::new(matrix)?;
+        Some(Self {
+            bounding_box: object_to_world.bounding_box(&geometry.bounding_box()),
+            geometry,
+            object_to_world,
+            material: None,
+        })
+    }
+
+    /// Replaces the materials of the geometry with a single material
+    pub fn with_material(self, material: Arc<dyn Material + Sync + Send>) -> Self {
+        Self {
+            material: Some(material),
+            ..self
+        }
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let record = self
+            .object_to_world
+            .hit(self.geometry.as_ref(), ray, ray_time)?;
+        Some(match &self.material {
+            Some(material) => record.with_material(material.clone()),
+            None => record,
+        })
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        self.object_to_world
+            .pdf_value(self.geometry.as_ref(), origin, direction)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        self.object_to_world.random(self.geometry.as_ref(), origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        hittable::{sphere::Sphere, testing::assert_sampling_integrates_to_one},
+        material::lambertian::Lambertian,
+        rng::reseed,
+    };
+
+    fn unit_sphere() -> Arc<dyn Hittable + Sync + Send> {
+        Arc::new(Sphere::new(
+            Point3::new([0.0; 3]),
+            1.0,
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        ))
+    }
+
+    #[test]
+    fn translated_sphere_matches_moved_sphere() {
+        let offset = Vec3::new([1.0, -2.0, 3.0]);
+        let instance = Instance::new(unit_sphere(), Matrix4::translation(offset)).unwrap();
+        let ray = Ray::new(
+            Point3::new([1.0, -2.0, -5.0]),
+            Vec3::new([0.0, 0.0, 1.0]),
+            0.0,
+        );
+        let record = instance
+            .hit(&ray, Interval::new(0.001, f64::INFINITY))
+            .unwrap();
+        assert!((record.time() - 7.0).abs() < 1e-9);
+        assert!((*record.point() - Point3::new([1.0, -2.0, 2.0])).near_zero());
+        assert!((*record.normal() - Vec3::new([0.0, 0.0, -1.0])).near_zero());
+    }
+
+    #[test]
+    fn scaled_sphere_normals_stay_perpendicular() {
+        // Ellipsoid x² / 4 + y² + z² = 1, whose gradient is (x / 4, y, z)
+        let matrix = Matrix4::scaling(Vec3::new([2.0, 1.0, 1.0]));
+        let instance = Instance::new(unit_sphere(), matrix).unwrap();
+        let ray = Ray::new(
+            Point3::new([1.0, 5.0, 0.0]),
+            Vec3::new([0.0, -1.0, 0.0]),
+            0.0,
+        );
+        let record = instance
+            .hit(&ray, Interval::new(0.001, f64::INFINITY))
+            .unwrap();
+        let point = *record.point();
+        let gradient = Vec3::new([point[0] / 4.0, point[1], point[2]]).unit_vector();
+        assert!((*record.normal() - gradient).near_zero());
+    }
+
+    #[test]
+    fn singular_matrix_is_rejected() {
+        let flat = Matrix4::scaling(Vec3::new([1.0, 0.0, 1.0]));
+        assert!(Instance::new(unit_sphere(), flat).is_none());
+    }
+
+    /// Ellipsoid scaled unevenly and rotated, seen from outside
+    fn ellipsoid() -> Instance {
+        let matrix = Matrix4::translation(Vec3::new([0.5, 0.0, 0.0]))
+            * Matrix4::rotation(Vec3::new([1.0, 1.0, 0.0]), 30.0)
+            * Matrix4::scaling(Vec3::new([2.0, 1.0, 0.5]));
+        Instance::new(unit_sphere(), matrix).unwrap()
+    }
+
+    #[test]
+    fn density_integrates_to_one() {
+        reseed(31);
+        assert_sampling_integrates_to_one(&ellipsoid(), Point3::new([0.0, 0.0, -3.0]));
+    }
+}
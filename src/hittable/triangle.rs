@@ -0,0 +1,288 @@
+use std::sync::Arc;
+
+use super::{HitRecord, Hittable, quad::area_pdf_value};
+use crate::{
+    aabb::Aabb,
+    interval::Interval,
+    material::Material,
+    ray::Ray,
+    rng::random,
+    vec3::{Point3, Vec3},
+};
+
+pub struct Triangle {
+    vertices: [Point3; 3],
+
+    /// Optional per-vertex shading normals, interpolated across the face
+    normals: Option<[Vec3; 3]>,
+
+    /// Optional per-vertex texture coordinates, interpolated across the face
+    uvs: Option<[[f64; 2]; 3]>,
+
+    material: Arc<dyn Material + Sync + Send>,
+    bounding_box: Aabb,
+}
+
+impl Triangle {
+    pub fn new(vertices: [Point3; 3], material: Arc<dyn Material + Sync + Send>) -> Self {
+        Self {
+            vertices,
+            normals: None,
+            uvs: None,
+            material,
+            bounding_box: bounding_box(&vertices),
+        }
+    }
+
+    /// Sets per-vertex normals, used for smooth shading. They don't need unit length.
+    pub fn with_normals(self, normals: [Vec3; 3]) -> Self {
+        Self {
+            normals: Some(normals),
+            ..self
+        }
+    }
+
+    /// Sets per-vertex texture coordinates
+    pub fn with_uvs(self, uvs: [[f64; 2]; 3]) -> Self {
+        Self {
+            uvs: Some(uvs),
+            ..self
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, ray_time: Interval) -> Option<HitRecord> {
+        let (time, barycentric) = intersect(&self.vertices, ray, ray_time)?;
+        Some(hit_record(
+            &self.vertices,
+            self.normals.as_ref(),
+            self.uvs.as_ref(),
+            ray,
+            time,
+            barycentric,
+            self.material.clone(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        let [a, b, c] = self.vertices;
+        let n = (b - a).cross(&(c - a));
+        area_pdf_value(self, 0.5 * n.length(), &n.unit_vector(), origin, direction)
+    }
+
+    fn random(&self, origin: &Point3) -> Vec3 {
+        // Fold the unit square onto the triangle, keeping points uniformly distributed
+        let (mut u, mut v) = (random::<f64>(), random::<f64>());
+        if u + v > 1.0 {
+            (u, v) = (1.0 - u, 1.0 - v);
+        }
+        let [a, b, c] = self.vertices;
+        a + u * (b - a) + v * (c - a) - *origin
+    }
+}
+
+pub(super) const fn bounding_box(vertices: &[Point3; 3]) -> Aabb {
+    Aabb::surrounding(
+        &Aabb::from_points(vertices[0], vertices[1]),
+        &Aabb::from_points(vertices[2], vertices[2]),
+    )
+}
+
+/// Möller–Trumbore ray/triangle intersection. Returns the ray time of the hit and the
+/// barycentric weights of the three vertices at the hit point.
+pub(super) fn intersect(
+    vertices: &[Point3; 3],
+    ray: &Ray,
+    ray_time: Interval,
+) -> Option<(f64, [f64; 3])> {
+    let edge1 = vertices[1] - vertices[0];
+    let edge2 = vertices[2] - vertices[0];
+    let p = ray.direction().cross(&edge2);
+    let determinant = edge1.dot(&p);
+
+    // The ray is parallel to the triangle plane
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+    let determinant_inverse = 1.0 / determinant;
+
+    let s = *ray.origin() - vertices[0];
+    let u = s.dot(&p) * determinant_inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let v = ray.direction().dot(&q) * determinant_inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let time = edge2.dot(&q) * determinant_inverse;
+    ray_time
+        .surrounds(time)
+        .then_some((time, [1.0 - u - v, u, v]))
+}
+
+/// Builds the hit record of a triangle hit, interpolating the optional vertex attributes
+pub(super) fn hit_record(
+    vertices: &[Point3; 3],
+    normals: Option<&[Vec3; 3]>,
+    uvs: Option<&[[f64; 2]; 3]>,
+    ray: &Ray,
+    time: f64,
+    barycentric: [f64; 3],
+    material: Arc<dyn Material + Sync + Send>,
+) -> HitRecord {
+    let geometric_normal = (vertices[1] - vertices[0])
+        .cross(&(vertices[2] - vertices[0]))
+        .unit_vector();
+    let normal = normals.map_or(geometric_normal, |normals| {
+        let normal = (0..3)
+            .map(|i| barycentric[i] * normals[i].unit_vector())
+            .sum::<Vec3>()
+            .unit_vector();
+
+        // Keep the shading normal on the same side as the geometric one
+        if normal.dot(&geometric_normal) < 0.0 {
+            -normal
+        } else {
+            normal
+        }
+    });
+    let uv = uvs.map_or([barycentric[1], barycentric[2]], |uvs| {
+        [0, 1].map(|axis| (0..3).map(|i| barycentric[i] * uvs[i][axis]).sum())
+    });
+    HitRecord::new(ray.at(time), normal, time, ray, material).with_uv(uv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color, hittable::testing::assert_sampling_integrates_to_one,
+        material::lambertian::Lambertian, rng::reseed,
+    };
+
+    fn unit_triangle() -> Triangle {
+        Triangle::new(
+            [
+                Point3::new([0.0, 0.0, 0.0]),
+                Point3::new([1.0, 0.0, 0.0]),
+                Point3::new([0.0, 1.0, 0.0]),
+            ],
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        )
+    }
+
+    /// Ray straight down onto the plane z = 0 through the point, from z = 1
+    fn downward_ray(x: f64, y: f64) -> Ray {
+        Ray::new(Point3::new([x, y, 1.0]), Vec3::new([0.0, 0.0, -1.0]), 0.0)
+    }
+
+    const RAY_TIME: Interval = Interval::new(0.001, f64::INFINITY);
+
+    #[test]
+    fn hits_inside_and_misses_outside() {
+        let triangle = unit_triangle();
+        let record = triangle.hit(&downward_ray(0.25, 0.25), RAY_TIME).unwrap();
+        assert_eq!(record.time(), 1.0);
+        assert_eq!(*record.point(), Point3::new([0.25, 0.25, 0.0]));
+        assert!(triangle.hit(&downward_ray(0.6, 0.6), RAY_TIME).is_none());
+        assert!(triangle.hit(&downward_ray(-0.1, 0.5), RAY_TIME).is_none());
+        assert!(
+            triangle
+                .hit(&downward_ray(0.25, 0.25), Interval::new(0.001, 0.5))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn edges_are_hit() {
+        let triangle = unit_triangle();
+        for [x, y] in [[0.5, 0.0], [0.0, 0.5], [0.5, 0.5]] {
+            assert!(triangle.hit(&downward_ray(x, y), RAY_TIME).is_some());
+        }
+    }
+
+    #[test]
+    fn back_face_normal_faces_the_ray() {
+        let ray = Ray::new(
+            Point3::new([0.25, 0.25, -1.0]),
+            Vec3::new([0.0, 0.0, 1.0]),
+            0.0,
+        );
+        let record = unit_triangle().hit(&ray, RAY_TIME).unwrap();
+        assert!(!record.front_face());
+        assert_eq!(*record.normal(), Vec3::new([0.0, 0.0, -1.0]));
+        let record = unit_triangle()
+            .hit(&downward_ray(0.25, 0.25), RAY_TIME)
+            .unwrap();
+        assert!(record.front_face());
+        assert_eq!(*record.normal(), Vec3::new([0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn uvs_are_interpolated_barycentrically() {
+        // The point has barycentric weights 0.25, 0.25 and 0.5
+        let ray = downward_ray(0.25, 0.5);
+        let record = unit_triangle().hit(&ray, RAY_TIME).unwrap();
+        assert_eq!(record.uv(), [0.25, 0.5]);
+
+        let uvs = [[0.2, 0.4], [1.0, 0.0], [0.6, 1.0]];
+        let record = unit_triangle().with_uvs(uvs).hit(&ray, RAY_TIME).unwrap();
+        let expected = [0.25 * 0.2 + 0.25 * 1.0 + 0.5 * 0.6, 0.25 * 0.4 + 0.5 * 1.0];
+        for (value, expected) in record.uv().into_iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn vertex_normals_are_interpolated_and_normalized() {
+        let normals = [
+            Vec3::new([1.0, 0.0, 1.0]),
+            Vec3::new([0.0, 2.0, 2.0]),
+            Vec3::new([0.0, 0.0, 3.0]),
+        ];
+        let triangle = unit_triangle().with_normals(normals);
+        let record = triangle.hit(&downward_ray(0.25, 0.5), RAY_TIME).unwrap();
+        let expected = (0.25 * normals[0].unit_vector()
+            + 0.25 * normals[1].unit_vector()
+            + 0.5 * normals[2].unit_vector())
+        .unit_vector();
+        assert!((record.normal().length() - 1.0).abs() < 1e-12);
+        assert!((*record.normal() - expected).length() < 1e-12);
+
+        // At a vertex the normal is the vertex normal
+        let record = triangle.hit(&downward_ray(1.0, 0.0), RAY_TIME).unwrap();
+        assert!((*record.normal() - normals[1].unit_vector()).length() < 1e-12);
+    }
+
+    #[test]
+    fn shading_normals_do_not_change_the_light_density() {
+        let flat = unit_triangle();
+        let smooth = unit_triangle().with_normals([
+            Vec3::new([1.0, 0.0, 1.0]),
+            Vec3::new([0.0, 1.0, 1.0]),
+            Vec3::new([-1.0, -1.0, 1.0]),
+        ]);
+        let origin = Point3::new([0.3, -0.5, 2.0]);
+        for target in [[0.2, 0.2, 0.0], [0.6, 0.1, 0.0], [0.1, 0.7, 0.0]] {
+            let direction = Point3::new(target) - origin;
+            let expected = flat.pdf_value(&origin, &direction);
+            assert!(expected > 0.0);
+            assert!((smooth.pdf_value(&origin, &direction) - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn density_integrates_to_one() {
+        reseed(55);
+        assert_sampling_integrates_to_one(&unit_triangle(), Point3::new([0.25, 0.25, 0.5]));
+    }
+}
@@ -0,0 +1,297 @@
+//! Path tracing with next-event estimation, combining light and material sampling with
+//! multiple importance sampling, and ending long paths by Russian roulette. Rays carrying
+//! wavelengths are traced spectrally, with the colors along their paths upsampled to values at
+//! the wavelengths.
+
+use crate::{
+    background::Background,
+    color::Color,
+    hittable::{HitRecord, Hittable, list::List},
+    interval::Interval,
+    material::Scatter,
+    pdf::Pdf,
+    ray::Ray,
+    rng::random,
+    spectrum::Wavelengths,
+    vec3::Point3,
+};
+
+/// Ray times closer than this are ignored, so rays don't hit the surface they leave
+const SELF_INTERSECTION_EPSILON: f64 = 0.001;
+
+/// Weight of a sample from a strategy with density `pdf` against another strategy with density
+/// `other_pdf`, by the power heuristic with exponent 2
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (pdf, other_pdf) = (pdf * pdf, other_pdf * other_pdf);
+    if pdf.is_infinite() {
+        1.0
+    } else {
+        pdf / (pdf + other_pdf)
+    }
+}
+
+/// Returns the values of the color at the wavelengths of a spectral ray, or the color itself
+fn spectral(wavelengths: Option<Wavelengths>, color: Color) -> Color {
+    wavelengths.map_or(color, |wavelengths| wavelengths.upsample(color))
+}
+
+/// How the vertex before a hit sampled the ray leading to it
+#[derive(Debug, Clone, Copy)]
+enum Sampled {
+    /// Camera rays and delta lobes, which light sampling can't find, so emission they hit
+    /// counts fully
+    Deterministic,
+
+    /// Material sampling with the density of the direction under both strategies, to weight
+    /// emission hit against light sampling
+    Material { material_pdf: f64, light_pdf: f64 },
+}
+
+/// Estimates the light arriving along rays through a scene
+pub struct PathTracer<'a> {
+    world: &'a (dyn Hittable + Sync + Send),
+
+    /// Emitting objects sampled directly at every diffuse vertex
+    lights: &'a List,
+
+    background: &'a Background,
+
+    /// Number of bounces after which paths are randomly terminated by Russian roulette
+    roulette_depth: u8,
+
+    /// Number of bounces after which paths always end
+    max_depth: u8,
+}
+
+impl<'a> PathTracer<'a> {
+    pub const fn new(
+        world: &'a (dyn Hittable + Sync + Send),
+        lights: &'a List,
+        background: &'a Background,
+        roulette_depth: u8,
+        max_depth: u8,
+    ) -> Self {
+        Self {
+            world,
+            lights,
+            background,
+            roulette_depth,
+            max_depth,
+        }
+    }
+
+    /// Returns the light arriving along the ray, at its wavelengths if it carries any.
+    ///
+    /// The path is extended one vertex at a time, keeping the throughput: the fraction of the
+    /// light found further along the path that reaches the start of the ray.
+    pub fn radiance(&self, mut ray: Ray) -> Color {
+        let wavelengths = ray.wavelengths();
+        let mut radiance = Color::default();
+        let mut throughput = Color::new([1.0; 3]);
+        let mut sampled = Sampled::Deterministic;
+        for depth in 0..self.max_depth {
+            let Some(record) = self.world.hit(
+                &ray,
+                Interval::new(SELF_INTERSECTION_EPSILON, f64::INFINITY),
+            ) else {
+                radiance +=
+                    throughput * spectral(wavelengths, self.background.color(ray.direction()));
+                break;
+            };
+
+            let material = record.material();
+            let emitted = spectral(wavelengths, material.emitted(&ray, &record));
+            radiance += throughput
+                * match sampled {
+                    Sampled::Deterministic => emitted,
+                    Sampled::Material {
+                        material_pdf,
+                        light_pdf,
+                    } => power_heuristic(material_pdf, light_pdf) * emitted,
+                };
+
+            let Some(scatter) = material.scatter(&ray, &record) else {
+                break;
+            };
+            (ray, sampled) = match scatter {
+                Scatter::Specular {
+                    ray: scattered,
+                    attenuation,
+                } => {
+                    throughput *= spectral(wavelengths, attenuation);
+
+                    // Scattering that doesn't depend on the wavelength keeps those of the ray
+                    let scattered = match (ray.wavelengths(), scattered.wavelengths()) {
+                        (Some(before), Some(after)) => {
+                            throughput *= before.termination_weight(&after);
+                            scattered
+                        }
+                        (before, _) => scattered.with_wavelengths(before),
+                    };
+                    (scattered, Sampled::Deterministic)
+                }
+                Scatter::Sampled { pdf } => {
+                    radiance += throughput * self.sample_light(&ray, &record, pdf.as_ref());
+
+                    let scattered = Ray::new(*record.point(), pdf.generate(), ray.shutter_time())
+                        .with_wavelengths(ray.wavelengths());
+                    let material_pdf = pdf.value(scattered.direction());
+                    if material_pdf <= 0.0 {
+                        break;
+                    }
+                    throughput *=
+                        spectral(wavelengths, material.scattering(&ray, &record, &scattered))
+                            / material_pdf;
+                    let sampled = Sampled::Material {
+                        material_pdf,
+                        light_pdf: self.light_pdf(record.point(), &scattered),
+                    };
+                    (scattered, sampled)
+                }
+            };
+
+            if depth + 1 >= self.roulette_depth {
+                // Continue with a probability following the throughput, dividing survivors by
+                // it so the estimate stays unbiased
+                let survival = throughput
+                    .x()
+                    .max(throughput.y())
+                    .max(throughput.z())
+                    .min(1.0);
+                if random::<f64>() >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+        }
+        radiance
+    }
+
+    /// Returns the density of sampling the direction of the ray toward the lights
+    fn light_pdf(&self, origin: &Point3, ray: &Ray) -> f64 {
+        if self.lights.is_empty() {
+            0.0
+        } else {
+            self.lights.pdf_value(origin, ray.direction())
+        }
+    }
+
+    /// Samples a direction toward the lights and traces a shadow ray along it, returning the
+    /// light scattered from there along the ray, weighted against material sampling
+    fn sample_light(&self, ray: &Ray, record: &HitRecord, material_sampling: &dyn Pdf) -> Color {
+        if self.lights.is_empty() {
+            return Color::default();
+        }
+        let origin = *record.point();
+        let shadow_ray = Ray::new(origin, self.lights.random(&origin), ray.shutter_time());
+        let light_pdf = self.light_pdf(&origin, &shadow_ray);
+        if light_pdf <= 0.0 {
+            return Color::default();
+        }
+        let scattering = spectral(
+            ray.wavelengths(),
+            record.material().scattering(ray, record, &shadow_ray),
+        );
+        if scattering == Color::default() {
+            return Color::default();
+        }
+
+        // Whatever the shadow ray hits first is what it sees, so occluders block the light
+        let Some(light_record) = self.world.hit(
+            &shadow_ray,
+            Interval::new(SELF_INTERSECTION_EPSILON, f64::INFINITY),
+        ) else {
+            return Color::default();
+        };
+        let emitted = spectral(
+            ray.wavelengths(),
+            light_record.material().emitted(&shadow_ray, &light_record),
+        );
+        let weight = power_heuristic(light_pdf, material_sampling.value(shadow_ray.direction()));
+        weight / light_pdf * scattering * emitted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        hittable::{quad::Quad, sphere::Sphere},
+        material::{diffuse_light::DiffuseLight, lambertian::Lambertian},
+        rng::reseed,
+        vec3::Vec3,
+    };
+
+    /// Gray floor lit by a small spherical light above it
+    fn floor_and_light() -> (List, List) {
+        let light = || {
+            Box::new(Sphere::new(
+                Point3::new([0.0, 2.0, 0.0]),
+                0.25,
+                Arc::new(DiffuseLight::new(Color::new([20.0; 3]))),
+            ))
+        };
+        let mut world = List::new(Box::new(Quad::new(
+            Point3::new([-5.0, 0.0, -5.0]),
+            Vec3::new([0.0, 0.0, 10.0]),
+            Vec3::new([10.0, 0.0, 0.0]),
+            Arc::new(Lambertian::new(Color::new([0.5; 3]))),
+        )));
+        world.add(light());
+        (world, List::new(light()))
+    }
+
+    fn mean_radiance(tracer: &PathTracer, ray: &Ray, samples: usize) -> f64 {
+        (0..samples)
+            .map(|_| tracer.radiance(ray.clone()).x())
+            .sum::<f64>()
+            / samples as f64
+    }
+
+    #[test]
+    fn light_sampling_keeps_the_estimate() {
+        let (world, lights) = floor_and_light();
+        let background = Background::Solid(Color::default());
+        let ray = Ray::new(
+            Point3::new([1.0, 1.0, 1.0]),
+            Vec3::new([-1.0, -1.0, -0.5]),
+            0.0,
+        );
+
+        reseed(3);
+        let no_lights = List::default();
+        let without_lights = PathTracer::new(&world, &no_lights, &background, 2, 8);
+        let expected = mean_radiance(&without_lights, &ray, 200_000);
+        let with_lights = PathTracer::new(&world, &lights, &background, 2, 8);
+        let estimate = mean_radiance(&with_lights, &ray, 20_000);
+        assert!(expected > 0.0);
+        assert!((estimate - expected).abs() < 0.03 * expected);
+    }
+
+    #[test]
+    fn russian_roulette_keeps_the_estimate() {
+        let (world, lights) = floor_and_light();
+        let background = Background::Solid(Color::new([0.2; 3]));
+        let ray = Ray::new(
+            Point3::new([1.0, 1.0, 1.0]),
+            Vec3::new([-1.0, -1.0, -0.5]),
+            0.0,
+        );
+
+        reseed(7);
+        let without_roulette = PathTracer::new(&world, &lights, &background, 8, 8);
+        let expected = mean_radiance(&without_roulette, &ray, 50_000);
+        let with_roulette = PathTracer::new(&world, &lights, &background, 1, 8);
+        let estimate = mean_radiance(&with_roulette, &ray, 50_000);
+        assert!((estimate - expected).abs() < 0.03 * expected);
+    }
+
+    #[test]
+    fn power_heuristic_weights_sum_to_one() {
+        for (a, b) in [(1.0, 1.0), (0.2, 3.0), (5.0, 0.0)] {
+            assert!((power_heuristic(a, b) + power_heuristic(b, a) - 1.0).abs() < 1e-12);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Interval {
     min: f64,
     max: f64,
@@ -26,6 +26,14 @@ impl Interval {
         self.max
     }
 
+    /// Creates the tightest interval enclosing both intervals
+    pub const fn enclosing(a: &Self, b: &Self) -> Self {
+        Self {
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }
+    }
+
     pub const fn with_min(self, min: f64) -> Self {
         Self { min, max: self.max }
     }
@@ -38,6 +46,12 @@ impl Interval {
         self.max - self.min
     }
 
+    /// Returns the interval grown by `delta` in total, half on each side
+    pub const fn expand(self, delta: f64) -> Self {
+        let padding = delta / 2.0;
+        Self::new(self.min - padding, self.max + padding)
+    }
+
     pub const fn contains(&self, value: f64) -> bool {
         self.min <= value && value <= self.max
     }
@@ -1,37 +1,56 @@
 #![warn(clippy::missing_const_for_fn)]
 
-use std::{sync::Arc, time::Instant};
+use std::{env, process::ExitCode, sync::Arc, time::Instant};
 
-use rand::{random, random_range};
+use rayon::ThreadPoolBuilder;
 
 use crate::{
     camera::Camera,
+    cli::{Arguments, USAGE},
     color::Color,
-    hittable::{Hittable, list::List as HittableList, sphere::Sphere},
+    hittable::{Hittable, bvh::BvhNode, list::List, plane::Plane, sphere::Sphere},
     material::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal},
+    rng::{random, random_range},
+    scene::Scene,
     vec3::{Point3, Vec3},
 };
 
+pub mod aabb;
+pub mod background;
 pub mod camera;
+pub mod cli;
 pub mod color;
+pub mod framebuffer;
 pub mod hittable;
+pub mod integrator;
 pub mod interval;
 pub mod material;
+pub mod matrix;
+pub mod microfacet;
+pub mod obj;
+pub mod onb;
+pub mod output;
+pub mod pdf;
 pub mod ray;
+pub mod rng;
+pub mod scene;
+pub mod spectrum;
+pub mod texture;
+pub mod tone_map;
 pub mod vec3;
+pub mod voxel_grid;
 
 const A_MAX: i8 = 11;
 const B_MAX: i8 = 11;
 
-fn main() {
-    let start = Instant::now();
-
+/// Creates the final scene of the book: a field of random small spheres around three big ones
+fn random_spheres_scene() -> Scene {
     // Create the world
-    let big_spheres: [Box<dyn Hittable + Sync>; 4] = [
+    let big_spheres: [Box<dyn Hittable + Sync + Send>; 4] = [
         // Ground
-        Box::new(Sphere::new(
-            Point3::new([0.0, -1000.0, 0.0]),
-            1000.0,
+        Box::new(Plane::new(
+            Point3::new([0.0; 3]),
+            Vec3::new([0.0, 1.0, 0.0]),
             Arc::new(Lambertian::new(Color::new([0.5; 3]))),
         )),
         Box::new(Sphere::new(
@@ -50,7 +69,7 @@ fn main() {
             Arc::new(Metal::new(Color::new([0.7, 0.6, 0.5]), 0.0)),
         )),
     ];
-    let world: Vec<Box<dyn Hittable + Sync>> = (-A_MAX..A_MAX)
+    let world: Vec<Box<dyn Hittable + Sync + Send>> = (-A_MAX..A_MAX)
         .flat_map(|a| (-B_MAX..B_MAX).map(move |b| (a, b)))
         .map(|(a, b)| {
             Point3::new([
@@ -60,7 +79,7 @@ fn main() {
             ])
         })
         .filter(|center| (*center - Point3::new([4.0, 0.2, 0.0])).length() > 0.9)
-        .map(|center| -> Box<dyn Hittable + Sync> {
+        .map(|center| -> Box<dyn Hittable + Sync + Send> {
             Box::new(Sphere::new(
                 center,
                 0.2,
@@ -79,7 +98,7 @@ fn main() {
         })
         .chain(big_spheres)
         .collect();
-    let world = HittableList::from(world);
+    let world = BvhNode::new(world);
 
     // Create the camera
     let camera = Camera::new(
@@ -95,8 +114,76 @@ fn main() {
         10.0,
     );
 
+    Scene {
+        camera,
+        world,
+        lights: List::default(),
+    }
+}
+
+fn main() -> ExitCode {
+    let start = Instant::now();
+
+    let arguments = match Arguments::parse(env::args_os().skip(1)) {
+        Ok(Some(arguments)) => arguments,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Some(threads) = arguments.threads
+        && let Err(error) = ThreadPoolBuilder::new().num_threads(threads).build_global()
+    {
+        eprintln!("error: {error}");
+        return ExitCode::FAILURE;
+    }
+    if let Some(seed) = arguments.seed {
+        rng::reseed(seed);
+    }
+
+    // Load the scene file given as argument, or fall back to the built-in scene
+    let scene = match &arguments.scene {
+        Some(path) => match Scene::load(path) {
+            Ok(scene) => scene,
+            Err(error) => {
+                eprintln!("error: {error}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => random_spheres_scene(),
+    };
+
+    // Apply the command-line overrides to the scene camera
+    let mut camera = scene.camera.with_seed(arguments.seed);
+    if let Some(aspect_ratio) = arguments.aspect_ratio {
+        camera = camera.with_aspect_ratio(aspect_ratio);
+    }
+    let image_width = arguments.image_width(camera.image_width());
+    if image_width != camera.image_width() {
+        camera = camera.with_image_width(image_width);
+    }
+    if let Some(samples_per_pixel) = arguments.samples_per_pixel {
+        camera = camera.with_samples_per_pixel(samples_per_pixel);
+    }
+    if let Some(max_depth) = arguments.max_depth {
+        camera = camera.with_max_depth(max_depth);
+    }
+    if arguments.spectral {
+        camera = camera.with_spectral(true);
+    }
+
     // Use the camera to make a picture of the world
-    camera.render(&world);
+    let framebuffer = camera.render(&scene.world, &scene.lights);
+    if let Err(error) = output::save(&framebuffer, &arguments.output, &arguments.tone_mapping) {
+        eprintln!("error: {error}");
+        return ExitCode::FAILURE;
+    }
 
     eprintln!("{:?}", start.elapsed());
+    ExitCode::SUCCESS
 }
@@ -1,9 +1,39 @@
-use crate::{color::Color, hittable::HitRecord, ray::Ray};
+use crate::{color::Color, hittable::HitRecord, pdf::Pdf, ray::Ray};
 
+pub mod conductor;
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
+pub mod principled;
+pub mod rough_dielectric;
+#[cfg(test)]
+pub(crate) mod testing;
+
+/// How a material scatters an incoming ray
+pub enum Scatter {
+    /// Scattering into a single direction, like a mirror or glass, which can't be importance
+    /// sampled
+    Specular { ray: Ray, attenuation: Color },
+
+    /// Scattering into directions distributed by a PDF, with the light scattered into each
+    /// direction given by [`Material::scattering`]
+    Sampled { pdf: Box<dyn Pdf> },
+}
 
 pub trait Material {
-    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(Ray, Color)>;
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<Scatter>;
+
+    /// Returns the fraction of light arriving along `scattered` that the material scatters back
+    /// along the ray, the BSDF times the cosine with the normal, for materials scattering with
+    /// a PDF
+    fn scattering(&self, _ray: &Ray, _record: &HitRecord, _scattered: &Ray) -> Color {
+        Color::default()
+    }
+
+    /// Returns the light emitted by the surface at the hit point, black for most materials
+    fn emitted(&self, _ray: &Ray, _record: &HitRecord) -> Color {
+        Color::default()
+    }
 }
@@ -0,0 +1,152 @@
+use std::array;
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    microfacet::Ggx,
+    onb::Onb,
+    pdf::ggx_reflection::GgxReflectionPdf,
+    ray::Ray,
+};
+
+/// Complex refractive index of a metal for red, green and blue light, with `eta` the real
+/// part and `k` the extinction coefficient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComplexIor {
+    pub eta: Color,
+    pub k: Color,
+}
+
+impl ComplexIor {
+    pub const GOLD: Self = Self::new([0.143, 0.374, 1.442], [3.983, 2.385, 1.603]);
+    pub const COPPER: Self = Self::new([0.200, 0.924, 1.102], [3.912, 2.452, 2.142]);
+    pub const ALUMINIUM: Self = Self::new([1.657, 0.880, 0.521], [9.224, 6.270, 4.837]);
+    pub const SILVER: Self = Self::new([0.155, 0.117, 0.138], [4.828, 3.122, 2.147]);
+
+    pub const fn new(eta: [f64; 3], k: [f64; 3]) -> Self {
+        Self {
+            eta: Color::new(eta),
+            k: Color::new(k),
+        }
+    }
+
+    /// Returns the reflectance of light arriving at the given cosine with the normal
+    pub fn reflectance(&self, cosine: f64) -> Color {
+        Color::new(array::from_fn(|channel| {
+            fresnel_conductor(cosine, self.eta[channel], self.k[channel])
+        }))
+    }
+}
+
+/// Exact Fresnel reflectance of unpolarized light at a conductor, from the outside
+fn fresnel_conductor(cosine: f64, eta: f64, k: f64) -> f64 {
+    let cos_squared = cosine.clamp(0.0, 1.0).powi(2);
+    let sin_squared = 1.0 - cos_squared;
+    let (eta_squared, k_squared) = (eta * eta, k * k);
+
+    let t0 = eta_squared - k_squared - sin_squared;
+    let a_squared_plus_b_squared = (t0 * t0 + 4.0 * eta_squared * k_squared).sqrt();
+    let t1 = a_squared_plus_b_squared + cos_squared;
+    let a = (0.5 * (a_squared_plus_b_squared + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cosine * a;
+    let perpendicular = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos_squared * a_squared_plus_b_squared + sin_squared * sin_squared;
+    let t4 = t2 * sin_squared;
+    let parallel = perpendicular * (t3 - t4) / (t3 + t4);
+
+    0.5 * (perpendicular + parallel)
+}
+
+/// Metal with GGX microfacets, reflecting with the Fresnel factor of its complex refractive
+/// index
+pub struct Conductor {
+    ior: ComplexIor,
+    ggx: Ggx,
+}
+
+impl Conductor {
+    /// Creates the conductor with a perceptual roughness between 0 for a mirror and 1
+    pub fn new(ior: ComplexIor, roughness: f64) -> Self {
+        Self {
+            ior,
+            ggx: Ggx::from_roughness(roughness),
+        }
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<Scatter> {
+        let outgoing = -ray.direction().unit_vector();
+        let cosine = outgoing.dot(record.normal());
+        if cosine <= 0.0 {
+            return None;
+        }
+
+        if self.ggx.is_smooth() {
+            return Some(Scatter::Specular {
+                ray: Ray::new(
+                    *record.point(),
+                    ray.direction().reflect(record.normal()),
+                    ray.shutter_time(),
+                ),
+                attenuation: self.ior.reflectance(cosine),
+            });
+        }
+        let basis = Onb::new(record.normal());
+        Some(Scatter::Sampled {
+            pdf: Box::new(GgxReflectionPdf::new(
+                basis,
+                basis.to_local(&outgoing),
+                self.ggx,
+            )),
+        })
+    }
+
+    fn scattering(&self, ray: &Ray, record: &HitRecord, scattered: &Ray) -> Color {
+        let basis = Onb::new(record.normal());
+        let outgoing = basis.to_local(&-ray.direction().unit_vector());
+        let incoming = basis.to_local(&scattered.direction().unit_vector());
+        if outgoing.z() <= 0.0 || incoming.z() <= 0.0 {
+            return Color::default();
+        }
+        let half_vector = (outgoing + incoming).unit_vector();
+
+        // The cosine with the normal cancels the incoming cosine of the BSDF denominator
+        self.ggx.distribution(&half_vector) * self.ggx.shadowing_masking(&outgoing, &incoming)
+            / (4.0 * outgoing.z())
+            * self.ior.reflectance(outgoing.dot(&half_vector))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::testing::{hit, sampled_albedo},
+        rng::reseed,
+    };
+
+    #[test]
+    fn normal_incidence_matches_closed_form() {
+        let (eta, k) = (0.2_f64, 3.9_f64);
+        let expected = ((eta - 1.0).powi(2) + k * k) / ((eta + 1.0).powi(2) + k * k);
+        assert!((fresnel_conductor(1.0, eta, k) - expected).abs() < 1e-12);
+        assert!((fresnel_conductor(0.0, eta, k) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rough_conductor_does_not_create_energy() {
+        reseed(4);
+        let material = Arc::new(Conductor::new(ComplexIor::SILVER, 0.5));
+        let (ray, record) = hit(material.clone(), 45.0, true);
+        let albedo = sampled_albedo(material.as_ref(), &ray, &record, 50_000);
+        for channel in 0..3 {
+            assert!(albedo[channel] <= 1.0, "{albedo:?}");
+            assert!(albedo[channel] > 0.8, "{albedo:?}");
+        }
+    }
+}
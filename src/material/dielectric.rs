@@ -1,52 +1,313 @@
-use rand::random;
+use std::array;
 
-use crate::{color::Color, material::Material, ray::Ray};
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    ray::Ray,
+    rng::random,
+};
+
+/// Wavelength in nanometres refractive indices are usually given at, the helium d line
+pub const REFERENCE_WAVELENGTH: f64 = 587.56;
+
+/// Refractive index varying with the wavelength of light, splitting white light into colors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dispersion {
+    /// Cauchy's equation n = A + B / λ², with λ in micrometres
+    Cauchy { a: f64, b: f64 },
+
+    /// Sellmeier equation n² = 1 + Σ Bᵢ λ² / (λ² - Cᵢ), with λ in micrometres
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// Borosilicate crown glass, the common optical glass of lenses and prisms
+    pub const BK7: Self = Self::Sellmeier {
+        b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+        c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+    };
+
+    pub const DIAMOND: Self = Self::Sellmeier {
+        b: [4.3356, 0.3306, 0.0],
+        c: [0.011_236, 0.030_625, 0.0],
+    };
+
+    /// Returns the refractive index for light of the wavelength in nanometres
+    pub fn refraction_index(&self, wavelength: f64) -> f64 {
+        let squared = (wavelength / 1000.0).powi(2);
+        match self {
+            Self::Cauchy { a, b } => a + b / squared,
+            Self::Sellmeier { b, c } => (1.0
+                + (0..3)
+                    .map(|i| b[i] * squared / (squared - c[i]))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+}
+
+/// Medium enclosed by the surface of a dielectric, which refracts and absorbs light
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interior {
+    /// Refractive index of the medium over the index of the enclosing media
+    pub refraction_index: f64,
+
+    /// Fraction of light absorbed per unit of distance travelled inside, for each channel
+    pub absorption: Color,
+}
+
+impl Interior {
+    pub const fn new(refraction_index: f64) -> Self {
+        Self {
+            refraction_index,
+            absorption: Color::new([0.0; 3]),
+        }
+    }
+
+    /// Returns the medium absorbing light travelling through it, tinting thick parts more than
+    /// thin parts
+    pub const fn with_absorption(self, absorption: Color) -> Self {
+        Self { absorption, ..self }
+    }
+
+    /// Returns the refractive index beyond the surface over the index on the side of the ray
+    pub const fn eta(&self, record: &HitRecord) -> f64 {
+        if record.front_face() {
+            self.refraction_index
+        } else {
+            1.0 / self.refraction_index
+        }
+    }
+
+    /// Returns the fraction of light left after travelling along the ray to the hit, by the
+    /// Beer–Lambert law. Only rays hitting the inside of the surface have travelled through
+    /// the medium, so light crossing other objects inside it before is not absorbed there.
+    pub fn transmittance(&self, ray: &Ray, record: &HitRecord) -> Color {
+        if record.front_face() || self.absorption == Color::default() {
+            return Color::new([1.0; 3]);
+        }
+        let distance = record.time() * ray.direction().length();
+        Color::new(array::from_fn(|channel| {
+            (-self.absorption[channel] * distance).exp()
+        }))
+    }
+}
 
 pub struct Dielectric {
-    // Refractive index in vacuum or air, or the ratio of the material's refractive index over
-    // the refractive index of the enclosing media.
-    refraction_index: f64,
+    /// Refractive index in vacuum or air, or the ratio of the material's refractive index over
+    /// the refractive index of the enclosing media, and absorption inside
+    interior: Interior,
+
+    /// Refractive index per wavelength for spectral rendering, which also gives the index of
+    /// the interior at the reference wavelength
+    dispersion: Option<Dispersion>,
 }
 
 impl Dielectric {
     pub const fn new(refraction_index: f64) -> Self {
-        Self { refraction_index }
+        Self {
+            interior: Interior::new(refraction_index),
+            dispersion: None,
+        }
+    }
+
+    /// Returns the dielectric with a refractive index varying with the wavelength, using its
+    /// index at the reference wavelength when rendering colors
+    pub fn with_dispersion(self, dispersion: Dispersion) -> Self {
+        Self {
+            interior: Interior {
+                refraction_index: dispersion.refraction_index(REFERENCE_WAVELENGTH),
+                ..self.interior
+            },
+            dispersion: Some(dispersion),
+        }
+    }
+
+    /// Returns the dielectric absorbing light travelling through it, see
+    /// [`Interior::with_absorption`]
+    pub const fn with_absorption(self, absorption: Color) -> Self {
+        Self {
+            interior: self.interior.with_absorption(absorption),
+            ..self
+        }
     }
+}
 
-    fn reflectance(cosine: f64, refraction_index: f64) -> f64 {
-        // Use schlick's approximation for reflectance
-        let r0 = (1.0 - refraction_index) / (1.0 + refraction_index);
-        let r0 = r0 * r0;
-        r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+/// Exact Fresnel reflectance of unpolarized light arriving at the cosine with the normal on an
+/// interface with relative refractive index `eta`, the index beyond it over the index before it
+pub fn fresnel_dielectric(cosine: f64, eta: f64) -> f64 {
+    let cos_incident = cosine.clamp(0.0, 1.0);
+    let sin_squared_transmitted = (1.0 - cos_incident * cos_incident) / (eta * eta);
+    if sin_squared_transmitted >= 1.0 {
+        // Total internal reflection
+        return 1.0;
     }
+    let cos_transmitted = (1.0 - sin_squared_transmitted).sqrt();
+    let parallel = (eta * cos_incident - cos_transmitted) / (eta * cos_incident + cos_transmitted);
+    let perpendicular =
+        (cos_incident - eta * cos_transmitted) / (cos_incident + eta * cos_transmitted);
+    0.5 * (parallel * parallel + perpendicular * perpendicular)
+}
+
+/// Returns the absorption coefficient leaving the color of white light after travelling the
+/// distance through a medium
+pub fn absorption_from_color(color: Color, distance: f64) -> Color {
+    Color::new(array::from_fn(|channel| -color[channel].ln() / distance))
 }
 
 impl Material for Dielectric {
-    fn scatter(
-        &self,
-        ray: &crate::ray::Ray,
-        record: &crate::hittable::HitRecord,
-    ) -> Option<(crate::ray::Ray, crate::color::Color)> {
-        let refraction_index = if record.front_face() {
-            1.0 / self.refraction_index
-        } else {
-            self.refraction_index
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<Scatter> {
+        // Dispersion refracts every wavelength differently, so only the hero wavelength
+        // follows the scattered ray
+        let (interior, wavelengths) = match (self.dispersion, ray.wavelengths()) {
+            (Some(dispersion), Some(wavelengths)) => (
+                Interior {
+                    refraction_index: dispersion.refraction_index(wavelengths.hero()),
+                    ..self.interior
+                },
+                Some(wavelengths.into_hero_only()),
+            ),
+            _ => (self.interior, None),
         };
+        let eta = interior.eta(record);
 
         let unit_direction = ray.direction().unit_vector();
         let cos_theta = (-unit_direction).dot(record.normal()).min(1.0);
-        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
-        let direction = if refraction_index * sin_theta > 1.0
-            || Self::reflectance(cos_theta, refraction_index) > random::<f64>().abs()
-        {
+        // The reflectance is one beyond the critical angle, for total internal reflection
+        let direction = if random::<f64>() < fresnel_dielectric(cos_theta, eta) {
             unit_direction.reflect(record.normal())
         } else {
-            unit_direction.refract(*record.normal(), refraction_index)
+            unit_direction.refract(*record.normal(), 1.0 / eta)
         };
 
-        let attenuation = Color::new([1.0; 3]);
-        let scattered = Ray::new(*record.point(), direction);
-        Some((scattered, attenuation))
+        Some(Scatter::Specular {
+            ray: Ray::new(*record.point(), direction, ray.shutter_time())
+                .with_wavelengths(wavelengths),
+            attenuation: interior.transmittance(ray, record),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::testing::hit,
+        rng::reseed,
+        spectrum::Wavelengths,
+        vec3::{Point3, Vec3},
+    };
+
+    /// Returns the attenuation of a ray hitting the glass after travelling the distance, from
+    /// inside or outside
+    fn attenuation(material: Arc<Dielectric>, distance: f64, inside: bool) -> Color {
+        let side = if inside { 1.0 } else { -1.0 };
+        let ray = Ray::new(
+            Point3::new([0.0, -side * distance, 0.0]),
+            Vec3::new([0.0, side * 0.5 * distance, 0.0]),
+            0.0,
+        );
+        let record = HitRecord::new(
+            Point3::new([0.0; 3]),
+            Vec3::new([0.0, 1.0, 0.0]),
+            2.0,
+            &ray,
+            material.clone(),
+        );
+        let Some(Scatter::Specular { attenuation, .. }) = material.scatter(&ray, &record) else {
+            panic!("dielectrics are specular");
+        };
+        attenuation
+    }
+
+    #[test]
+    fn thick_glass_absorbs_more_than_thin_glass() {
+        let color = Color::new([0.8, 0.5, 0.2]);
+        let material =
+            Arc::new(Dielectric::new(1.5).with_absorption(absorption_from_color(color, 2.0)));
+
+        // Light entering the glass has not travelled through it yet
+        assert_eq!(
+            attenuation(material.clone(), 2.0, false),
+            Color::new([1.0; 3])
+        );
+        let thin = attenuation(material.clone(), 2.0, true);
+        let thick = attenuation(material, 4.0, true);
+        for channel in 0..3 {
+            assert!((thin[channel] - color[channel]).abs() < 1e-12, "{thin:?}");
+            assert!(
+                (thick[channel] - color[channel].powi(2)).abs() < 1e-12,
+                "{thick:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn clear_glass_does_not_absorb() {
+        let material = Arc::new(Dielectric::new(1.5));
+        assert_eq!(attenuation(material, 100.0, true), Color::new([1.0; 3]));
+    }
+
+    #[test]
+    fn reflectance_follows_the_fresnel_equations() {
+        reseed(25);
+        let material = Arc::new(Dielectric::new(1.5));
+        let reflected_fraction = |degrees: f64, front_face: bool| {
+            let (ray, record) = hit(material.clone(), degrees, front_face);
+            let samples = 100_000;
+            (0..samples)
+                .filter(|_| {
+                    let Some(Scatter::Specular { ray: scattered, .. }) =
+                        material.scatter(&ray, &record)
+                    else {
+                        panic!("dielectrics are specular");
+                    };
+                    scattered.direction().dot(record.normal()) > 0.0
+                })
+                .count() as f64
+                / samples as f64
+        };
+        let expected = fresnel_dielectric(70_f64.to_radians().cos(), 1.5);
+        let fraction = reflected_fraction(70.0, true);
+        assert!((fraction - expected).abs() < 0.005, "{fraction} {expected}");
+        // Beyond the critical angle of about 42° from inside
+        assert_eq!(reflected_fraction(45.0, false), 1.0);
+    }
+
+    #[test]
+    fn dispersion_matches_catalog_indices() {
+        assert!((Dispersion::BK7.refraction_index(REFERENCE_WAVELENGTH) - 1.5168).abs() < 1e-4);
+        assert!((Dispersion::DIAMOND.refraction_index(REFERENCE_WAVELENGTH) - 2.417).abs() < 1e-3);
+        let cauchy = Dispersion::Cauchy { a: 1.5, b: 0.004 };
+        assert!((cauchy.refraction_index(500.0) - 1.516).abs() < 1e-12);
+        for dispersion in [Dispersion::BK7, Dispersion::DIAMOND, cauchy] {
+            assert!(dispersion.refraction_index(450.0) > dispersion.refraction_index(650.0));
+        }
+    }
+
+    #[test]
+    fn dispersion_refracts_the_hero_wavelength() {
+        reseed(24);
+        let material = Arc::new(Dielectric::new(1.0).with_dispersion(Dispersion::DIAMOND));
+        let (ray, record) = hit(material.clone(), 0.6_f64.asin().to_degrees(), true);
+        let (direction, normal) = (*ray.direction(), *record.normal());
+        for _ in 0..100 {
+            let wavelengths = Wavelengths::sample();
+            let ray = ray.clone().with_wavelengths(Some(wavelengths));
+            let Some(Scatter::Specular { ray: scattered, .. }) = material.scatter(&ray, &record)
+            else {
+                panic!("dielectrics are specular");
+            };
+            assert_eq!(scattered.wavelengths(), Some(wavelengths.into_hero_only()));
+            if scattered.direction().y() < 0.0 {
+                let index = Dispersion::DIAMOND.refraction_index(wavelengths.hero());
+                let expected = direction.refract(normal, 1.0 / index);
+                assert!((scattered.direction().unit_vector() - expected).length() < 1e-9);
+            }
+        }
     }
 }
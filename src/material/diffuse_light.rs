@@ -0,0 +1,61 @@
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    ray::Ray,
+};
+
+/// Light emitting surface, scattering no light itself
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub const fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _ray: &Ray, _record: &HitRecord) -> Option<Scatter> {
+        None
+    }
+
+    fn emitted(&self, _ray: &Ray, record: &HitRecord) -> Color {
+        // Only emit from the outside of the surface
+        if record.front_face() {
+            self.emit
+        } else {
+            Color::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::material::testing::hit;
+
+    #[test]
+    fn emits_only_from_the_front() {
+        let emit = Color::new([4.0, 3.0, 2.0]);
+        let material = Arc::new(DiffuseLight::new(emit));
+        for degrees in [0.0, 45.0, 89.0] {
+            let (ray, record) = hit(material.clone(), degrees, true);
+            assert_eq!(material.emitted(&ray, &record), emit);
+            let (ray, record) = hit(material.clone(), degrees, false);
+            assert_eq!(material.emitted(&ray, &record), Color::default());
+        }
+    }
+
+    #[test]
+    fn scatters_nothing() {
+        let material = Arc::new(DiffuseLight::new(Color::new([1.0; 3])));
+        for front_face in [true, false] {
+            let (ray, record) = hit(material.clone(), 30.0, front_face);
+            assert!(material.scatter(&ray, &record).is_none());
+        }
+    }
+}
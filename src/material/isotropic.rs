@@ -0,0 +1,37 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    pdf::sphere::SpherePdf,
+    ray::Ray,
+    texture::{Texture, solid_color::SolidColor},
+};
+
+/// Phase function of participating media, scattering equally in all directions
+pub struct Isotropic {
+    texture: Arc<dyn Texture + Sync + Send>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self { texture }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, _ray: &Ray, _record: &HitRecord) -> Option<Scatter> {
+        Some(Scatter::Sampled {
+            pdf: Box::new(SpherePdf),
+        })
+    }
+
+    fn scattering(&self, _ray: &Ray, record: &HitRecord, _scattered: &Ray) -> Color {
+        self.texture.value(record.uv(), record.point()) / (4.0 * PI)
+    }
+}
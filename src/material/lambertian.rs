@@ -1,21 +1,37 @@
-use crate::{color::Color, hittable::HitRecord, material::Material, ray::Ray, vec3::Vec3};
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    pdf::cosine::CosinePdf,
+    ray::Ray,
+    texture::{Texture, solid_color::SolidColor},
+};
 
 pub struct Lambertian {
-    albedo: Color,
+    texture: Arc<dyn Texture + Sync + Send>,
 }
 
 impl Lambertian {
-    pub const fn new(albedo: Color) -> Self {
-        Self { albedo }
+    pub fn new(albedo: Color) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self { texture }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray: &Ray, record: &HitRecord) -> Option<(Ray, Color)> {
-        let mut scatter_direction = *record.normal() + Vec3::random_unit_vector();
-        if scatter_direction.near_zero() {
-            scatter_direction = *record.normal();
-        }
-        Some((Ray::new(*record.point(), scatter_direction), self.albedo))
+    fn scatter(&self, _ray: &Ray, record: &HitRecord) -> Option<Scatter> {
+        Some(Scatter::Sampled {
+            pdf: Box::new(CosinePdf::new(record.normal())),
+        })
+    }
+
+    fn scattering(&self, _ray: &Ray, record: &HitRecord, scattered: &Ray) -> Color {
+        let cosine = record.normal().dot(&scattered.direction().unit_vector());
+        (cosine / PI).max(0.0) * self.texture.value(record.uv(), record.point())
     }
 }
@@ -1,24 +1,40 @@
-use crate::{color::Color, hittable::HitRecord, material::Material, ray::Ray, vec3::Vec3};
+use std::sync::Arc;
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    ray::Ray,
+    texture::{Texture, solid_color::SolidColor},
+    vec3::Vec3,
+};
 
 pub struct Metal {
-    albedo: Color,
+    texture: Arc<dyn Texture + Sync + Send>,
     fuzz: f64,
 }
 
 impl Metal {
-    pub const fn new(albedo: Color, fuzz: f64) -> Self {
+    pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)), fuzz)
+    }
+
+    pub fn from_texture(texture: Arc<dyn Texture + Sync + Send>, fuzz: f64) -> Self {
         Self {
-            albedo,
+            texture,
             fuzz: fuzz.min(1.0),
         }
     }
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<(crate::ray::Ray, Color)> {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<Scatter> {
         let reflected = ray.direction().reflect(record.normal()).unit_vector()
             + (self.fuzz * Vec3::random_unit_vector());
-        let scattered = Ray::new(*record.point(), reflected);
-        (scattered.direction().dot(record.normal()) > 0.0).then_some((scattered, self.albedo))
+        let scattered = Ray::new(*record.point(), reflected, ray.shutter_time());
+        (scattered.direction().dot(record.normal()) > 0.0).then(|| Scatter::Specular {
+            ray: scattered,
+            attenuation: self.texture.value(record.uv(), record.point()),
+        })
     }
 }
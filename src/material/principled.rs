@@ -0,0 +1,449 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    color::{Color, luminance},
+    hittable::HitRecord,
+    material::{
+        Material, Scatter,
+        dielectric::{Interior, fresnel_dielectric},
+    },
+    microfacet::{Ggx, ggx_dielectric_scattering},
+    onb::Onb,
+    pdf::{
+        Pdf, cosine::CosinePdf, ggx_dielectric::GgxDielectricPdf, ggx_reflection::GgxReflectionPdf,
+        mixture::WeightedMixturePdf,
+    },
+    ray::Ray,
+    texture::{Texture, solid_color::SolidColor},
+};
+
+/// Lowest roughness, keeping the specular lobes wide enough to be sampled with a PDF, so they
+/// can be mixed with the diffuse lobe
+const MIN_ROUGHNESS: f64 = 0.032;
+
+/// Roughness of the clearcoat layer
+const CLEARCOAT_ROUGHNESS: f64 = 0.1;
+
+/// Refractive index of the clearcoat layer, which reflects 4% of the light at normal incidence
+const CLEARCOAT_REFRACTION_INDEX: f64 = 1.5;
+
+/// Uber material after the Disney principled BSDF (Burley 2012 and 2015), layering a diffuse
+/// base with sheen, specular reflection, glass-like transmission and a clearcoat.
+///
+/// Every parameter is a texture. Scalar parameters read the mean of its channels, so gray
+/// textures map directly to values, which are between 0 and 1 except for the refractive index.
+pub struct Principled {
+    base_color: Arc<dyn Texture + Sync + Send>,
+
+    /// Blends from a dielectric to a metal reflecting with the base color
+    metallic: Arc<dyn Texture + Sync + Send>,
+
+    /// Perceptual roughness of the specular and transmission lobes
+    roughness: Arc<dyn Texture + Sync + Send>,
+
+    /// Reflectance of dielectrics, with 0.5 reflecting 4% at normal incidence
+    specular: Arc<dyn Texture + Sync + Send>,
+
+    /// Blends the specular reflection of dielectrics from white to the hue of the base color
+    specular_tint: Arc<dyn Texture + Sync + Send>,
+
+    /// Extra reflection at grazing angles, for cloth
+    sheen: Arc<dyn Texture + Sync + Send>,
+
+    /// Strength of a glossy transparent layer on top
+    clearcoat: Arc<dyn Texture + Sync + Send>,
+
+    /// Blends the diffuse base of dielectrics into transmission tinted by the base color
+    transmission: Arc<dyn Texture + Sync + Send>,
+
+    /// Refractive index of the transmitting material over the index of the enclosing media
+    refraction_index: Arc<dyn Texture + Sync + Send>,
+}
+
+/// Parameters looked up at a hit point
+struct Parameters {
+    base_color: Color,
+    metallic: f64,
+    ggx: Ggx,
+    specular: f64,
+    specular_tint: f64,
+    sheen: f64,
+    clearcoat: f64,
+    transmission: f64,
+    interior: Interior,
+}
+
+impl Parameters {
+    /// Weight of the diffuse base and the dielectric specular reflection over it
+    fn diffuse_weight(&self) -> f64 {
+        (1.0 - self.metallic) * (1.0 - self.transmission)
+    }
+
+    /// Weight of the glass-like lobe reflecting off and transmitting through the surface
+    fn glass_weight(&self) -> f64 {
+        (1.0 - self.metallic) * self.transmission
+    }
+
+    /// Refractive index giving the specular reflectance of dielectrics, 1 without specular
+    fn specular_refraction_index(&self) -> f64 {
+        let normal_reflectance = (0.08 * self.specular).sqrt();
+        (1.0 + normal_reflectance) / (1.0 - normal_reflectance)
+    }
+
+    /// Returns the specular reflectance of dielectrics for light arriving at the cosine with
+    /// the normal
+    fn dielectric_reflectance(&self, cosine: f64) -> f64 {
+        fresnel_dielectric(cosine, self.specular_refraction_index())
+    }
+
+    /// Returns the color of the specular reflection of dielectrics
+    fn specular_color(&self) -> Color {
+        let luminance = luminance(self.base_color);
+        let tint = if luminance > 0.0 {
+            self.base_color / luminance
+        } else {
+            Color::new([1.0; 3])
+        };
+        (1.0 - self.specular_tint) * Color::new([1.0; 3]) + self.specular_tint * tint
+    }
+
+    /// Returns the reflectance of metals for light arriving at the cosine with the normal, by
+    /// Schlick's approximation from the base color at normal incidence
+    fn metal_reflectance(&self, cosine: f64) -> Color {
+        let grazing = (1.0 - cosine.clamp(0.0, 1.0)).powi(5);
+        self.base_color + grazing * (Color::new([1.0; 3]) - self.base_color)
+    }
+
+    /// Returns the reflectance of the clearcoat for light arriving at the cosine with the
+    /// normal
+    fn clearcoat_reflectance(&self, cosine: f64) -> f64 {
+        self.clearcoat * fresnel_dielectric(cosine, CLEARCOAT_REFRACTION_INDEX)
+    }
+}
+
+impl Principled {
+    /// Creates a rough dielectric with the color, with the defaults of the Disney BSDF:
+    /// roughness and specular 0.5 and a refractive index of 1.5
+    pub fn new(base_color: Color) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(base_color)))
+    }
+
+    pub fn from_texture(base_color: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self {
+            base_color,
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            specular_tint: constant(0.0),
+            sheen: constant(0.0),
+            clearcoat: constant(0.0),
+            transmission: constant(0.0),
+            refraction_index: constant(1.5),
+        }
+    }
+
+    pub fn with_metallic(self, metallic: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self { metallic, ..self }
+    }
+
+    pub fn with_roughness(self, roughness: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self { roughness, ..self }
+    }
+
+    pub fn with_specular(self, specular: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self { specular, ..self }
+    }
+
+    pub fn with_specular_tint(self, specular_tint: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self {
+            specular_tint,
+            ..self
+        }
+    }
+
+    pub fn with_sheen(self, sheen: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self { sheen, ..self }
+    }
+
+    pub fn with_clearcoat(self, clearcoat: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self { clearcoat, ..self }
+    }
+
+    pub fn with_transmission(self, transmission: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self {
+            transmission,
+            ..self
+        }
+    }
+
+    pub fn with_refraction_index(self, refraction_index: Arc<dyn Texture + Sync + Send>) -> Self {
+        Self {
+            refraction_index,
+            ..self
+        }
+    }
+
+    fn parameters(&self, record: &HitRecord) -> Parameters {
+        let scalar = |texture: &Arc<dyn Texture + Sync + Send>| {
+            let color = texture.value(record.uv(), record.point());
+            (color.x() + color.y() + color.z()) / 3.0
+        };
+        let fraction = |texture| scalar(texture).clamp(0.0, 1.0);
+        Parameters {
+            base_color: self.base_color.value(record.uv(), record.point()),
+            metallic: fraction(&self.metallic),
+            ggx: Ggx::from_roughness(fraction(&self.roughness).max(MIN_ROUGHNESS)),
+            specular: fraction(&self.specular),
+            specular_tint: fraction(&self.specular_tint),
+            sheen: fraction(&self.sheen),
+            clearcoat: fraction(&self.clearcoat),
+            transmission: fraction(&self.transmission),
+            interior: Interior::new(scalar(&self.refraction_index).max(f64::EPSILON)),
+        }
+    }
+}
+
+/// Returns a texture with the same value everywhere
+fn constant(value: f64) -> Arc<dyn Texture + Sync + Send> {
+    Arc::new(SolidColor::new(Color::new([value; 3])))
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<Scatter> {
+        let basis = Onb::new(record.normal());
+        let outgoing = basis.to_local(&-ray.direction().unit_vector());
+        if outgoing.z() <= 0.0 {
+            return None;
+        }
+        let parameters = self.parameters(record);
+
+        // Sample each lobe about as often as it scatters light
+        let diffuse_weight = parameters.diffuse_weight();
+        let specular_weight =
+            parameters.metallic + diffuse_weight * parameters.dielectric_reflectance(outgoing.z());
+        let lobes: [(f64, Box<dyn Pdf>); 4] = [
+            (diffuse_weight, Box::new(CosinePdf::new(record.normal()))),
+            (
+                specular_weight,
+                Box::new(GgxReflectionPdf::new(basis, outgoing, parameters.ggx)),
+            ),
+            (
+                parameters.clearcoat_reflectance(outgoing.z()),
+                Box::new(GgxReflectionPdf::new(
+                    basis,
+                    outgoing,
+                    Ggx::from_roughness(CLEARCOAT_ROUGHNESS),
+                )),
+            ),
+            (
+                parameters.glass_weight(),
+                Box::new(GgxDielectricPdf::new(
+                    basis,
+                    outgoing,
+                    parameters.interior.eta(record),
+                    parameters.ggx,
+                )),
+            ),
+        ];
+        Some(Scatter::Sampled {
+            pdf: Box::new(WeightedMixturePdf::new(lobes)),
+        })
+    }
+
+    fn scattering(&self, ray: &Ray, record: &HitRecord, scattered: &Ray) -> Color {
+        let basis = Onb::new(record.normal());
+        let outgoing = basis.to_local(&-ray.direction().unit_vector());
+        let incoming = basis.to_local(&scattered.direction().unit_vector());
+        if outgoing.z() <= 0.0 {
+            return Color::default();
+        }
+        let parameters = self.parameters(record);
+
+        // Glass-like lobe, tinting the transmitted light by the base color
+        let glass = parameters.glass_weight()
+            * ggx_dielectric_scattering(
+                &parameters.ggx,
+                parameters.interior.eta(record),
+                &outgoing,
+                &incoming,
+            );
+        let mut base = if incoming.z() < 0.0 {
+            glass * parameters.base_color
+        } else {
+            Color::new([glass; 3])
+        };
+        if incoming.z() <= 0.0 {
+            return (1.0 - parameters.clearcoat_reflectance(outgoing.z())) * base;
+        }
+
+        // Diffuse, less the light reflected by the specular layer over it, with sheen toward
+        // grazing angles
+        let half_vector = (outgoing + incoming).unit_vector();
+        let outgoing_dot_half = outgoing.dot(&half_vector);
+        let diffuse =
+            (1.0 - parameters.dielectric_reflectance(outgoing.z())) * parameters.base_color / PI
+                + Color::new([parameters.sheen * (1.0 - outgoing_dot_half).powi(5); 3]);
+        base += parameters.diffuse_weight() * incoming.z() * diffuse;
+
+        // Specular reflection, colored by the base color for metals. The cosine with the
+        // normal cancels the incoming cosine of the BSDF denominator.
+        let microfacet = |ggx: &Ggx| {
+            ggx.distribution(&half_vector) * ggx.shadowing_masking(&outgoing, &incoming)
+                / (4.0 * outgoing.z())
+        };
+        let reflectance = parameters.diffuse_weight()
+            * parameters.dielectric_reflectance(outgoing_dot_half)
+            * parameters.specular_color()
+            + parameters.metallic * parameters.metal_reflectance(outgoing_dot_half);
+        base += microfacet(&parameters.ggx) * reflectance;
+
+        // The clearcoat reflects light before it reaches the base
+        (1.0 - parameters.clearcoat_reflectance(outgoing.z())) * base
+            + Color::new(
+                [parameters.clearcoat_reflectance(outgoing_dot_half)
+                    * microfacet(&Ggx::from_roughness(CLEARCOAT_ROUGHNESS)); 3],
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::{
+            dielectric::Dielectric,
+            lambertian::Lambertian,
+            metal::Metal,
+            testing::{hit, integrated_albedo, sampled_albedo, samples},
+        },
+        rng::reseed,
+        vec3::Vec3,
+    };
+
+    const SAMPLES: usize = 100_000;
+
+    fn mean(weights: impl Iterator<Item = Color>) -> Color {
+        weights.sum::<Color>() / SAMPLES as f64
+    }
+
+    #[test]
+    fn matte_dielectric_is_lambertian() {
+        reseed(12);
+        let albedo = Color::new([0.8, 0.5, 0.2]);
+        let material = Arc::new(Principled::new(albedo).with_specular(constant(0.0)));
+        let lambertian = Lambertian::new(albedo);
+        let (ray, record) = hit(material.clone(), 30.0, true);
+        for _ in 0..1000 {
+            let scattered = Ray::new(*record.point(), Vec3::random_unit_vector(), 0.0);
+            let difference = material.scattering(&ray, &record, &scattered)
+                - lambertian.scattering(&ray, &record, &scattered);
+            assert!(difference.length() < 1e-12, "{difference:?}");
+        }
+    }
+
+    #[test]
+    fn smooth_metallic_is_metal() {
+        reseed(13);
+        let albedo = Color::new([0.9, 0.6, 0.3]);
+        let material = Arc::new(
+            Principled::new(albedo)
+                .with_metallic(constant(1.0))
+                .with_roughness(constant(0.0)),
+        );
+        let (ray, record) = hit(material.clone(), 20.0, true);
+        let Some(Scatter::Specular {
+            ray: mirrored,
+            attenuation,
+        }) = Metal::new(albedo, 0.0).scatter(&ray, &record)
+        else {
+            panic!("smooth metals are specular");
+        };
+
+        let samples = samples(material.as_ref(), &ray, &record, SAMPLES);
+        let mirror = mirrored.direction().unit_vector();
+        let near_mirror = samples
+            .iter()
+            .filter(|(direction, _)| direction.dot(&mirror) > 0.999)
+            .count();
+        assert!(near_mirror as f64 > 0.99 * SAMPLES as f64);
+        let albedo = mean(samples.into_iter().map(|(_, weight)| weight));
+        assert!((albedo - attenuation).length() < 0.02, "{albedo:?}");
+    }
+
+    #[test]
+    fn smooth_transmission_is_dielectric() {
+        reseed(14);
+        let material = Arc::new(
+            Principled::new(Color::new([1.0; 3]))
+                .with_roughness(constant(0.0))
+                .with_transmission(constant(1.0)),
+        );
+        let dielectric = Dielectric::new(1.5);
+        let (ray, record) = hit(material.clone(), 40.0, true);
+        let mut refracted = Vec3::default();
+        let reflected_fraction = (0..SAMPLES)
+            .filter(|_| {
+                let Some(Scatter::Specular { ray: scattered, .. }) =
+                    dielectric.scatter(&ray, &record)
+                else {
+                    panic!("smooth dielectrics are specular");
+                };
+                let reflected = scattered.direction().dot(record.normal()) > 0.0;
+                if !reflected {
+                    refracted = scattered.direction().unit_vector();
+                }
+                reflected
+            })
+            .count() as f64
+            / SAMPLES as f64;
+
+        let samples = samples(material.as_ref(), &ray, &record, SAMPLES);
+        let (reflected, transmitted): (Vec<_>, Vec<_>) = samples
+            .into_iter()
+            .partition(|(direction, _)| direction.dot(record.normal()) > 0.0);
+        assert!(
+            (reflected.len() as f64 / SAMPLES as f64 - reflected_fraction).abs() < 0.01,
+            "{} {reflected_fraction}",
+            reflected.len()
+        );
+        let near_refracted = transmitted
+            .iter()
+            .filter(|(direction, _)| direction.dot(&refracted) > 0.999)
+            .count();
+        assert!(
+            near_refracted as f64 > 0.99 * transmitted.len() as f64,
+            "{near_refracted} {}",
+            transmitted.len()
+        );
+
+        // Unlike `Dielectric`, the radiance of transmitted light is divided by the square of
+        // the refractive index, as its solid angle changes
+        let reflected_albedo = mean(reflected.into_iter().map(|(_, weight)| weight)).x();
+        let transmitted_albedo = mean(transmitted.into_iter().map(|(_, weight)| weight)).x();
+        assert!((reflected_albedo - reflected_fraction).abs() < 0.01);
+        assert!((transmitted_albedo * 1.5 * 1.5 - (1.0 - reflected_fraction)).abs() < 0.02);
+    }
+
+    #[test]
+    fn sampling_matches_the_bsdf() {
+        reseed(15);
+        let material = Arc::new(
+            Principled::new(Color::new([0.7, 0.4, 0.6]))
+                .with_metallic(constant(0.3))
+                .with_roughness(constant(0.6))
+                .with_specular_tint(constant(0.5))
+                .with_sheen(constant(0.5))
+                .with_transmission(constant(0.4)),
+        );
+        let (ray, record) = hit(material.clone(), 50.0, true);
+        let sampled = sampled_albedo(material.as_ref(), &ray, &record, SAMPLES);
+        let integrated = integrated_albedo(material.as_ref(), &ray, &record, SAMPLES);
+        assert!(
+            (sampled - integrated).length() < 0.03,
+            "{sampled:?} {integrated:?}"
+        );
+        for channel in 0..3 {
+            assert!(sampled[channel] <= 1.0, "{sampled:?}");
+        }
+    }
+}
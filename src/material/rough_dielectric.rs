@@ -0,0 +1,141 @@
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::{
+        Material, Scatter,
+        dielectric::{Interior, fresnel_dielectric},
+    },
+    microfacet::{Ggx, ggx_dielectric_scattering},
+    onb::Onb,
+    pdf::ggx_dielectric::GgxDielectricPdf,
+    ray::Ray,
+    rng::random,
+};
+
+/// Frosted glass, reflecting off and refracting through GGX microfacets (Walter et al. 2007)
+pub struct RoughDielectric {
+    /// Refractive index and absorption of the material, relative to the enclosing media
+    interior: Interior,
+
+    ggx: Ggx,
+}
+
+impl RoughDielectric {
+    /// Creates the dielectric with a perceptual roughness between 0 for smooth glass and 1
+    pub fn new(refraction_index: f64, roughness: f64) -> Self {
+        Self {
+            interior: Interior::new(refraction_index),
+            ggx: Ggx::from_roughness(roughness),
+        }
+    }
+
+    /// Returns the dielectric absorbing light travelling through it, see
+    /// [`Interior::with_absorption`]
+    pub const fn with_absorption(self, absorption: Color) -> Self {
+        Self {
+            interior: self.interior.with_absorption(absorption),
+            ..self
+        }
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray: &Ray, record: &HitRecord) -> Option<Scatter> {
+        let eta = self.interior.eta(record);
+        let outgoing = -ray.direction().unit_vector();
+        if !self.ggx.is_smooth() {
+            let basis = Onb::new(record.normal());
+            return Some(Scatter::Sampled {
+                pdf: Box::new(GgxDielectricPdf::new(
+                    basis,
+                    basis.to_local(&outgoing),
+                    eta,
+                    self.ggx,
+                )),
+            });
+        }
+
+        // Smooth interface, choosing between reflection and refraction by the reflectance
+        let cosine = outgoing.dot(record.normal()).min(1.0);
+        let direction = if random::<f64>() < fresnel_dielectric(cosine, eta) {
+            ray.direction().reflect(record.normal())
+        } else {
+            (-outgoing).refract(*record.normal(), 1.0 / eta)
+        };
+        Some(Scatter::Specular {
+            ray: Ray::new(*record.point(), direction, ray.shutter_time()),
+            attenuation: self.interior.transmittance(ray, record),
+        })
+    }
+
+    fn scattering(&self, ray: &Ray, record: &HitRecord, scattered: &Ray) -> Color {
+        let basis = Onb::new(record.normal());
+        let value = ggx_dielectric_scattering(
+            &self.ggx,
+            self.interior.eta(record),
+            &basis.to_local(&-ray.direction().unit_vector()),
+            &basis.to_local(&scattered.direction().unit_vector()),
+        );
+        value * self.interior.transmittance(ray, record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        material::testing::{hit, integrated_albedo, sampled_albedo},
+        rng::reseed,
+    };
+
+    const SAMPLES: usize = 200_000;
+
+    /// Returns the material hit from the outside or the inside by a ray at 40° from the normal
+    fn frosted_glass(front_face: bool) -> (Arc<RoughDielectric>, Ray, HitRecord) {
+        let material = Arc::new(RoughDielectric::new(1.5, 0.5));
+        let (ray, record) = hit(material.clone(), 40.0, front_face);
+        (material, ray, record)
+    }
+
+    #[test]
+    fn sampling_matches_the_bsdf() {
+        reseed(8);
+        for front_face in [true, false] {
+            let (material, ray, record) = frosted_glass(front_face);
+            let sampled = sampled_albedo(material.as_ref(), &ray, &record, SAMPLES).x();
+            let integrated = integrated_albedo(material.as_ref(), &ray, &record, SAMPLES).x();
+            assert!(
+                (sampled - integrated).abs() < 0.05,
+                "{sampled} {integrated}"
+            );
+        }
+    }
+
+    #[test]
+    fn transmitted_radiance_follows_the_refractive_index() {
+        reseed(9);
+        let (material, ray, record) = frosted_glass(true);
+        let albedo = sampled_albedo(material.as_ref(), &ray, &record, SAMPLES).x();
+        // Seen from outside, light from inside the glass is spread over a larger solid angle,
+        // dividing its radiance by the square of the refractive index. Roughness loses a
+        // little more to light scattering between microfacets.
+        let reflectance = fresnel_dielectric(40_f64.to_radians().cos(), 1.5);
+        let smooth_albedo = reflectance + (1.0 - reflectance) / (1.5 * 1.5);
+        assert!(
+            (smooth_albedo - 0.06..smooth_albedo + 0.01).contains(&albedo),
+            "{albedo} {smooth_albedo}"
+        );
+    }
+
+    #[test]
+    fn smooth_glass_is_specular() {
+        let material = RoughDielectric::new(1.5, 0.0);
+        let (_, ray, record) = frosted_glass(true);
+        assert!(matches!(
+            material.scatter(&ray, &record),
+            Some(Scatter::Specular { .. })
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+//! Fixtures shared by the tests of the materials
+
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    material::{Material, Scatter},
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Returns a ray hitting the material at the origin at the angle in degrees with the normal
+/// `+y`, from the outside or the inside, and the hit
+pub fn hit(
+    material: Arc<dyn Material + Sync + Send>,
+    degrees: f64,
+    front_face: bool,
+) -> (Ray, HitRecord) {
+    let side = if front_face { 1.0 } else { -1.0 };
+    let angle = degrees.to_radians();
+    let ray = Ray::new(
+        Point3::new([-angle.sin(), side * angle.cos(), 0.0]),
+        Vec3::new([angle.sin(), -side * angle.cos(), 0.0]),
+        0.0,
+    );
+    let record = HitRecord::new(
+        Point3::new([0.0; 3]),
+        Vec3::new([0.0, 1.0, 0.0]),
+        1.0,
+        &ray,
+        material,
+    );
+    (ray, record)
+}
+
+/// Samples scattered rays from the PDF of the material, returning their unit directions with
+/// their scattering over the density
+pub fn samples(
+    material: &dyn Material,
+    ray: &Ray,
+    record: &HitRecord,
+    count: usize,
+) -> Vec<(Vec3, Color)> {
+    let Some(Scatter::Sampled { pdf }) = material.scatter(ray, record) else {
+        panic!("the material doesn't scatter with a PDF");
+    };
+    (0..count)
+        .map(|_| {
+            let scattered = Ray::new(*record.point(), pdf.generate(), 0.0);
+            let density = pdf.value(scattered.direction());
+            let weight = if density > 0.0 {
+                material.scattering(ray, record, &scattered) / density
+            } else {
+                Color::default()
+            };
+            (scattered.direction().unit_vector(), weight)
+        })
+        .collect()
+}
+
+/// Fraction of the light scattered, by importance sampling the material
+pub fn sampled_albedo(
+    material: &dyn Material,
+    ray: &Ray,
+    record: &HitRecord,
+    count: usize,
+) -> Color {
+    samples(material, ray, record, count)
+        .into_iter()
+        .map(|(_, weight)| weight)
+        .sum::<Color>()
+        / count as f64
+}
+
+/// Fraction of the light scattered, by integrating over all directions uniformly
+pub fn integrated_albedo(
+    material: &dyn Material,
+    ray: &Ray,
+    record: &HitRecord,
+    count: usize,
+) -> Color {
+    (0..count)
+        .map(|_| {
+            let scattered = Ray::new(*record.point(), Vec3::random_unit_vector(), 0.0);
+            material.scattering(ray, record, &scattered)
+        })
+        .sum::<Color>()
+        * 4.0
+        * PI
+        / count as f64
+}
@@ -0,0 +1,145 @@
+use std::{array, ops::Mul};
+
+use crate::vec3::{Point3, Vec3};
+
+/// 4x4 matrix for affine transforms of points and vectors, in row-major order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix4([[f64; 4]; 4]);
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(array::from_fn(|row| {
+            array::from_fn(|column| (0..4).map(|i| self.0[row][i] * rhs.0[i][column]).sum())
+        }))
+    }
+}
+
+impl Matrix4 {
+    pub const IDENTITY: Matrix4 = Matrix4([
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+
+    pub const fn new(values: [[f64; 4]; 4]) -> Self {
+        Self(values)
+    }
+
+    pub const fn translation(offset: Vec3) -> Self {
+        Self([
+            [1.0, 0.0, 0.0, offset.x()],
+            [0.0, 1.0, 0.0, offset.y()],
+            [0.0, 0.0, 1.0, offset.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub const fn scaling(factors: Vec3) -> Self {
+        Self([
+            [factors.x(), 0.0, 0.0, 0.0],
+            [0.0, factors.y(), 0.0, 0.0],
+            [0.0, 0.0, factors.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counter-clockwise rotation by `degrees` around `axis`, when looking down the axis
+    pub fn rotation(axis: Vec3, degrees: f64) -> Self {
+        let axis = axis.unit_vector();
+        let (x, y, z) = (axis.x(), axis.y(), axis.z());
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        Self([
+            [
+                t * x * x + cos,
+                t * x * y - sin * z,
+                t * x * z + sin * y,
+                0.0,
+            ],
+            [
+                t * x * y + sin * z,
+                t * y * y + cos,
+                t * y * z - sin * x,
+                0.0,
+            ],
+            [
+                t * x * z - sin * y,
+                t * y * z + sin * x,
+                t * z * z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self(array::from_fn(|row| {
+            array::from_fn(|column| self.0[column][row])
+        }))
+    }
+
+    /// Returns the determinant of the upper left 3x3 part, the factor by which an affine
+    /// transform scales volumes
+    pub fn linear_determinant(&self) -> f64 {
+        let m = &self.0;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Returns the inverse using Gauss-Jordan elimination, or `None` for a singular matrix
+    pub fn inverse(&self) -> Option<Self> {
+        let mut left = self.0;
+        let mut right = Self::IDENTITY.0;
+        for column in 0..4 {
+            // Partial pivoting, swap in the row with the largest value in this column
+            let pivot = (column..4)
+                .max_by(|&a, &b| left[a][column].abs().total_cmp(&left[b][column].abs()))
+                .unwrap();
+            if left[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            left.swap(column, pivot);
+            right.swap(column, pivot);
+
+            let scale = 1.0 / left[column][column];
+            for i in 0..4 {
+                left[column][i] *= scale;
+                right[column][i] *= scale;
+            }
+            for row in (0..4).filter(|&row| row != column) {
+                let factor = left[row][column];
+                for i in 0..4 {
+                    left[row][i] -= factor * left[column][i];
+                    right[row][i] -= factor * right[column][i];
+                }
+            }
+        }
+        Some(Self(right))
+    }
+
+    /// Transforms a position, applying the translation
+    pub fn transform_point(&self, point: &Point3) -> Point3 {
+        let m = &self.0;
+        Point3::new(array::from_fn(|row| {
+            m[row][0] * point.x() + m[row][1] * point.y() + m[row][2] * point.z() + m[row][3]
+        }))
+    }
+
+    /// Transforms a direction, ignoring the translation
+    pub fn transform_vector(&self, vector: &Vec3) -> Vec3 {
+        let m = &self.0;
+        Vec3::new(array::from_fn(|row| {
+            m[row][0] * vector.x() + m[row][1] * vector.y() + m[row][2] * vector.z()
+        }))
+    }
+}
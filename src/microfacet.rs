@@ -0,0 +1,213 @@
+//! GGX (Trowbridge-Reitz) microfacet distribution, in a local frame with the macro surface
+//! normal along +z
+
+use std::f64::consts::PI;
+
+use crate::{material::dielectric::fresnel_dielectric, rng::random, vec3::Vec3};
+
+/// Roughness below which a surface is treated as perfectly smooth, scattering as a delta lobe
+pub const SMOOTH_ALPHA: f64 = 1e-3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ggx {
+    /// Width of the distribution, the square of the perceptual roughness
+    alpha: f64,
+}
+
+impl Ggx {
+    /// Creates the distribution for a perceptual roughness between 0 and 1
+    pub fn from_roughness(roughness: f64) -> Self {
+        let roughness = roughness.clamp(0.0, 1.0);
+        Self {
+            alpha: roughness * roughness,
+        }
+    }
+
+    pub const fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Returns true if the surface is too smooth to sample with the distribution
+    pub fn is_smooth(&self) -> bool {
+        self.alpha < SMOOTH_ALPHA
+    }
+
+    /// Returns the density of microfacet normals, per unit solid angle projected onto the
+    /// macro surface
+    pub fn distribution(&self, normal: &Vec3) -> f64 {
+        if normal.z() <= 0.0 {
+            return 0.0;
+        }
+        let alpha_squared = self.alpha * self.alpha;
+        let denominator = normal.z() * normal.z() * (alpha_squared - 1.0) + 1.0;
+        alpha_squared / (PI * denominator * denominator)
+    }
+
+    /// Smith Λ, the ratio of microfacet area hidden from the direction to its visible area
+    fn lambda(&self, direction: &Vec3) -> f64 {
+        let cos_squared = direction.z() * direction.z();
+        if cos_squared <= 0.0 {
+            return f64::INFINITY;
+        }
+        let tan_squared = (1.0 - cos_squared).max(0.0) / cos_squared;
+        0.5 * ((1.0 + self.alpha * self.alpha * tan_squared).sqrt() - 1.0)
+    }
+
+    /// Smith masking, the fraction of microfacets visible from the direction
+    pub fn masking(&self, direction: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(direction))
+    }
+
+    /// Height-correlated Smith shadowing-masking, the fraction of microfacets visible from
+    /// both directions
+    pub fn shadowing_masking(&self, outgoing: &Vec3, incoming: &Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(outgoing) + self.lambda(incoming))
+    }
+
+    /// Samples a microfacet normal visible from the direction, which must be above the
+    /// surface (Heitz 2018, "Sampling the GGX Distribution of Visible Normals")
+    pub fn sample_visible_normal(&self, outgoing: &Vec3) -> Vec3 {
+        // Stretch the view direction to the hemisphere configuration
+        let view = Vec3::new([
+            self.alpha * outgoing.x(),
+            self.alpha * outgoing.y(),
+            outgoing.z(),
+        ])
+        .unit_vector();
+
+        let length_squared = view.x() * view.x() + view.y() * view.y();
+        let t1 = if length_squared > 0.0 {
+            Vec3::new([-view.y(), view.x(), 0.0]) / length_squared.sqrt()
+        } else {
+            Vec3::new([1.0, 0.0, 0.0])
+        };
+        let t2 = view.cross(&t1);
+
+        // Sample the projected area of the hemisphere, with its lower half foreshortened
+        let radius = random::<f64>().sqrt();
+        let phi = 2.0 * PI * random::<f64>();
+        let p1 = radius * phi.cos();
+        let s = 0.5 * (1.0 + view.z());
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * radius * phi.sin();
+        let hemisphere_normal =
+            p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * view;
+
+        // Unstretch back to the ellipsoid configuration
+        Vec3::new([
+            self.alpha * hemisphere_normal.x(),
+            self.alpha * hemisphere_normal.y(),
+            hemisphere_normal.z().max(0.0),
+        ])
+        .unit_vector()
+    }
+
+    /// Returns the density of sampling the microfacet normal with
+    /// [`Ggx::sample_visible_normal`] from the direction
+    pub fn visible_normal_pdf(&self, outgoing: &Vec3, normal: &Vec3) -> f64 {
+        if outgoing.z() <= 0.0 {
+            return 0.0;
+        }
+        self.masking(outgoing) * outgoing.dot(normal).max(0.0) * self.distribution(normal)
+            / outgoing.z()
+    }
+}
+
+/// Returns the microfacet normal scattering `outgoing` into `incoming`, on the side of
+/// `outgoing`, which must be above the surface. For reflections this is the half vector, for
+/// transmissions into a medium with relative refractive index `eta` its generalization by
+/// Walter et al. 2007. Returns `None` if no microfacet facing both directions does it.
+pub fn scattering_normal(outgoing: &Vec3, incoming: &Vec3, eta: f64) -> Option<Vec3> {
+    let eta = if incoming.z() > 0.0 { 1.0 } else { eta };
+    let normal = *outgoing + eta * *incoming;
+    if normal.length_squared() == 0.0 {
+        return None;
+    }
+    let normal = normal.unit_vector();
+    let normal = if normal.z() < 0.0 { -normal } else { normal };
+
+    // Reflections must leave the microfacet on the front, transmissions on the back
+    (normal.dot(outgoing) > 0.0 && normal.dot(incoming) * incoming.z() > 0.0).then_some(normal)
+}
+
+/// Returns the BSDF times the cosine of the incoming direction of GGX microfacets on an
+/// interface with relative refractive index `eta`, for unit directions in a local frame with
+/// the normal along +z on the side of `outgoing`
+pub fn ggx_dielectric_scattering(ggx: &Ggx, eta: f64, outgoing: &Vec3, incoming: &Vec3) -> f64 {
+    if outgoing.z() <= 0.0 {
+        return 0.0;
+    }
+    let Some(normal) = scattering_normal(outgoing, incoming, eta) else {
+        return 0.0;
+    };
+
+    let outgoing_dot_normal = outgoing.dot(&normal);
+    let reflectance = fresnel_dielectric(outgoing_dot_normal, eta);
+    let distribution = ggx.distribution(&normal);
+    let shadowing_masking = ggx.shadowing_masking(outgoing, incoming);
+
+    // The cosine of the incoming direction cancels in the denominator of the BSDF
+    if incoming.z() > 0.0 {
+        reflectance * distribution * shadowing_masking / (4.0 * outgoing.z())
+    } else {
+        let incoming_dot_normal = incoming.dot(&normal);
+        let denominator = outgoing_dot_normal + eta * incoming_dot_normal;
+        (1.0 - reflectance)
+            * distribution
+            * shadowing_masking
+            * (incoming_dot_normal * outgoing_dot_normal).abs()
+            / (outgoing.z() * denominator * denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::reseed;
+
+    const SAMPLES: usize = 100_000;
+
+    fn outgoing() -> Vec3 {
+        Vec3::new([0.6, 0.0, 0.8])
+    }
+
+    fn uniform_hemisphere() -> Vec3 {
+        Vec3::random_on_hemisphere(&Vec3::new([0.0, 0.0, 1.0]))
+    }
+
+    #[test]
+    fn visible_normals_are_normalized() {
+        reseed(1);
+        for roughness in [0.6, 0.8, 1.0] {
+            let ggx = Ggx::from_roughness(roughness);
+            let integral = (0..SAMPLES)
+                .map(|_| ggx.visible_normal_pdf(&outgoing(), &uniform_hemisphere()))
+                .sum::<f64>()
+                * 2.0
+                * PI
+                / SAMPLES as f64;
+            assert!((integral - 1.0).abs() < 0.03, "{roughness}: {integral}");
+        }
+    }
+
+    #[test]
+    fn visible_normal_samples_follow_their_pdf() {
+        reseed(2);
+        let ggx = Ggx::from_roughness(0.6);
+        // Mean of the squared z of the normals, by sampling them and by integrating their
+        // density over the hemisphere
+        let sampled = (0..SAMPLES)
+            .map(|_| ggx.sample_visible_normal(&outgoing()).z().powi(2))
+            .sum::<f64>()
+            / SAMPLES as f64;
+        let integrated = (0..SAMPLES)
+            .map(|_| {
+                let normal = uniform_hemisphere();
+                normal.z().powi(2) * ggx.visible_normal_pdf(&outgoing(), &normal)
+            })
+            .sum::<f64>()
+            * 2.0
+            * PI
+            / SAMPLES as f64;
+        assert!((sampled - integrated).abs() < 0.02);
+    }
+}
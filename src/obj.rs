@@ -0,0 +1,334 @@
+//! Wavefront OBJ mesh loader, with materials from companion MTL files
+
+use std::{
+    collections::HashMap,
+    error,
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    color::Color,
+    hittable::{
+        list::List,
+        mesh::{Face, Mesh, MeshBuffers},
+    },
+    material::{Material, lambertian::Lambertian},
+    vec3::Vec3,
+};
+
+pub mod mtl;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A file could not be opened or read
+    Io { path: PathBuf, source: io::Error },
+
+    /// A line could not be understood
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{line}: {message}", path.display()),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+/// Line-by-line reader keeping track of where it is, to report errors
+struct Lines<'a> {
+    path: &'a Path,
+    line_number: usize,
+    lines: io::Lines<BufReader<File>>,
+}
+
+impl<'a> Lines<'a> {
+    fn open(path: &'a Path) -> Result<Self, Error> {
+        let file = File::open(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Ok(Self {
+            path,
+            line_number: 0,
+            lines: BufReader::new(file).lines(),
+        })
+    }
+
+    /// Returns the next line split into its keyword and arguments, skipping blank lines and
+    /// comments
+    fn next_statement(&mut self) -> Result<Option<(String, Vec<String>)>, Error> {
+        for line in self.lines.by_ref() {
+            self.line_number += 1;
+            let line = line.map_err(|source| Error::Io {
+                path: self.path.to_owned(),
+                source,
+            })?;
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace().map(str::to_owned);
+            if let Some(keyword) = words.next() {
+                return Ok(Some((keyword, words.collect())));
+            }
+        }
+        Ok(None)
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::Parse {
+            path: self.path.to_owned(),
+            line: self.line_number,
+            message: message.into(),
+        }
+    }
+
+    /// Parses between `MIN` and `N` finite numbers, filling missing trailing values with
+    /// `default`
+    fn parse_numbers<const MIN: usize, const N: usize>(
+        &self,
+        arguments: &[String],
+        default: f64,
+    ) -> Result<[f64; N], Error> {
+        if !(MIN..=N).contains(&arguments.len()) {
+            return Err(self.error(format!(
+                "expected {MIN} to {N} numbers, found {}",
+                arguments.len()
+            )));
+        }
+        let mut numbers = [default; N];
+        for (number, argument) in numbers.iter_mut().zip(arguments) {
+            *number = argument
+                .parse()
+                .ok()
+                .filter(|number: &f64| number.is_finite())
+                .ok_or_else(|| self.error(format!("invalid number `{argument}`")))?;
+        }
+        Ok(numbers)
+    }
+}
+
+/// Faces grouped by the name of the material they use
+type FaceGroups = Vec<(Option<String>, Vec<Face>)>;
+
+/// Loads an OBJ file into one mesh per material, all sharing the same vertex buffers.
+///
+/// Polygons with more than three vertices are triangulated as fans. Materials are read from
+/// the MTL files referenced by `mtllib`, faces without a material are light gray diffuse.
+pub fn load(path: impl AsRef<Path>) -> Result<List, Error> {
+    let path = path.as_ref();
+    let mut lines = Lines::open(path)?;
+    let mut buffers = MeshBuffers::default();
+    let mut materials = HashMap::new();
+    let mut groups: FaceGroups = vec![(None, Vec::new())];
+
+    while let Some((keyword, arguments)) = lines.next_statement()? {
+        match keyword.as_str() {
+            "v" => {
+                let [x, y, z, w] = lines.parse_numbers::<3, 4>(&arguments, 1.0)?;
+                if w == 0.0 {
+                    return Err(lines.error("vertex weight must not be 0"));
+                }
+                buffers.positions.push(Vec3::new([x, y, z]) / w);
+            }
+            "vn" => {
+                let normal = lines.parse_numbers::<3, 3>(&arguments, 0.0)?;
+                buffers.normals.push(Vec3::new(normal));
+            }
+            "vt" => {
+                let [u, v, _] = lines.parse_numbers::<1, 3>(&arguments, 0.0)?;
+                buffers.uvs.push([u, v]);
+            }
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err(lines.error("a face needs at least 3 vertices"));
+                }
+                let vertices = arguments
+                    .iter()
+                    .map(|vertex| parse_face_vertex(&lines, &buffers, vertex))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let faces = &mut groups.last_mut().unwrap().1;
+                faces.extend((1..vertices.len() - 1).map(|i| {
+                    let corners = [vertices[0], vertices[i], vertices[i + 1]];
+                    Face {
+                        positions: corners.map(|corner| corner[0].unwrap()),
+                        uvs: corners
+                            .iter()
+                            .all(|corner| corner[1].is_some())
+                            .then(|| corners.map(|corner| corner[1].unwrap())),
+                        normals: corners
+                            .iter()
+                            .all(|corner| corner[2].is_some())
+                            .then(|| corners.map(|corner| corner[2].unwrap())),
+                    }
+                }));
+            }
+            "mtllib" => {
+                let directory = path.parent().unwrap_or(Path::new(""));
+                for file in &arguments {
+                    materials.extend(mtl::load(directory.join(file))?);
+                }
+            }
+            "usemtl" => {
+                let [name] = arguments.as_slice() else {
+                    return Err(lines.error("expected a single material name"));
+                };
+                if !materials.contains_key(name) {
+                    return Err(lines.error(format!("unknown material `{name}`")));
+                }
+                groups.push((Some(name.clone()), Vec::new()));
+            }
+            // Groups, objects, smoothing groups, lines, points and free-form geometry don't
+            // affect rendering
+            _ => {}
+        }
+    }
+
+    let default_material: Arc<dyn Material + Sync + Send> =
+        Arc::new(Lambertian::new(Color::new([0.8; 3])));
+    let buffers = Arc::new(buffers);
+    let mut list = List::default();
+    for (name, faces) in groups {
+        if faces.is_empty() {
+            continue;
+        }
+        let material =
+            name.map_or_else(|| default_material.clone(), |name| materials[&name].clone());
+        list.add(Box::new(Mesh::new(buffers.clone(), faces, material)));
+    }
+    Ok(list)
+}
+
+/// Parses a `v`, `v/vt`, `v//vn` or `v/vt/vn` face vertex into zero based indices.
+/// Negative indices count back from the most recently defined vertex.
+fn parse_face_vertex(
+    lines: &Lines,
+    buffers: &MeshBuffers,
+    vertex: &str,
+) -> Result<[Option<usize>; 3], Error> {
+    let lengths = [
+        buffers.positions.len(),
+        buffers.uvs.len(),
+        buffers.normals.len(),
+    ];
+    let mut indices = [None; 3];
+    let parts: Vec<_> = vertex.split('/').collect();
+    if parts.len() > 3 || parts[0].is_empty() {
+        return Err(lines.error(format!("invalid face vertex `{vertex}`")));
+    }
+    for (i, part) in parts.into_iter().enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        let index: isize = part
+            .parse()
+            .map_err(|_| lines.error(format!("invalid index `{part}` in `{vertex}`")))?;
+        let index = match index {
+            1.. => index.unsigned_abs() - 1,
+            ..0 => lengths[i].wrapping_sub(index.unsigned_abs()),
+            0 => usize::MAX,
+        };
+        if index >= lengths[i] {
+            return Err(lines.error(format!("index out of range in `{vertex}`")));
+        }
+        indices[i] = Some(index);
+    }
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::hittable::Hittable;
+
+    /// Writes the OBJ text to a temporary file and loads it
+    fn load_text(name: &str, text: &str) -> Result<List, Error> {
+        let path = std::env::temp_dir().join(format!("obj_test_{name}_{}.obj", std::process::id()));
+        fs::write(&path, text).unwrap();
+        let result = load(&path);
+        fs::remove_file(&path).unwrap();
+        result
+    }
+
+    /// Returns the line and message of a parse error
+    fn parse_error(result: Result<List, Error>) -> (usize, String) {
+        match result {
+            Err(Error::Parse { line, message, .. }) => (line, message),
+            Err(error) => panic!("expected a parse error, found {error}"),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    #[test]
+    fn negative_indices_count_back() {
+        let negative = load_text("negative", &format!("{TRIANGLE}v 5 5 5\nf -4 -3 -2\n")).unwrap();
+        let positive = load_text("positive", &format!("{TRIANGLE}f 1 2 3\n")).unwrap();
+        assert_eq!(negative.bounding_box(), positive.bounding_box());
+    }
+
+    #[test]
+    fn out_of_range_index_is_reported_with_its_line() {
+        let (line, message) = parse_error(load_text("range", &format!("{TRIANGLE}f 1 2 4\n")));
+        assert_eq!(line, 4);
+        assert!(message.contains("out of range"), "{message}");
+    }
+
+    #[test]
+    fn index_zero_is_rejected() {
+        let (line, message) = parse_error(load_text("zero", &format!("{TRIANGLE}f 0 1 2\n")));
+        assert_eq!(line, 4);
+        assert!(message.contains("out of range"), "{message}");
+    }
+
+    #[test]
+    fn faces_need_three_vertices() {
+        let (line, message) = parse_error(load_text("short", &format!("{TRIANGLE}f 1 2\n")));
+        assert_eq!(line, 4);
+        assert!(message.contains("at least 3 vertices"), "{message}");
+    }
+
+    #[test]
+    fn unknown_material_is_rejected() {
+        let (line, message) = parse_error(load_text("usemtl", "usemtl missing\n"));
+        assert_eq!(line, 1);
+        assert!(message.contains("unknown material `missing`"), "{message}");
+    }
+
+    #[test]
+    fn zero_weight_vertices_are_rejected() {
+        let (line, message) = parse_error(load_text("weight", "v 1 2 3 0\n"));
+        assert_eq!(line, 1);
+        assert!(message.contains("weight"), "{message}");
+    }
+
+    #[test]
+    fn infinite_positions_are_rejected() {
+        let (line, message) = parse_error(load_text("infinite", "v 0 0 0\nv inf 0 0\n"));
+        assert_eq!(line, 2);
+        assert!(message.contains("invalid number `inf`"), "{message}");
+    }
+}
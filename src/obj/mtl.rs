@@ -0,0 +1,257 @@
+//! Wavefront MTL material library loader
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use super::{Error, Lines};
+use crate::{
+    color::Color,
+    material::{Material, dielectric::Dielectric, lambertian::Lambertian, metal::Metal},
+};
+
+/// Parameters of a single `newmtl` block
+struct Parameters {
+    /// Diffuse color, `Kd`
+    diffuse: Color,
+
+    /// Specular color, `Ks`
+    specular: Color,
+
+    /// Specular exponent, `Ns`
+    specular_exponent: f64,
+
+    /// Index of refraction, `Ni`
+    refraction_index: f64,
+
+    /// Opacity, `d` or one minus `Tr`
+    dissolve: f64,
+
+    /// Illumination model, `illum`
+    illumination: u8,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            diffuse: Color::new([0.8; 3]),
+            specular: Color::default(),
+            specular_exponent: 0.0,
+            refraction_index: 1.0,
+            dissolve: 1.0,
+            illumination: 2,
+        }
+    }
+}
+
+impl Parameters {
+    /// Picks the closest of the available materials for the MTL parameters
+    fn into_material(self) -> Arc<dyn Material + Sync + Send> {
+        match self.illumination {
+            4 | 6 | 7 | 9 => Arc::new(Dielectric::new(self.refraction_index)),
+            _ if self.dissolve < 1.0 => Arc::new(Dielectric::new(self.refraction_index)),
+            3 | 5 | 8 => {
+                // Convert the Phong exponent to a roughness, sharp highlights mean little fuzz
+                let fuzz = (2.0 / (self.specular_exponent + 2.0)).sqrt();
+                Arc::new(Metal::new(self.specular, fuzz))
+            }
+            _ => Arc::new(Lambertian::new(self.diffuse)),
+        }
+    }
+}
+
+/// Loads all materials of an MTL file by name
+pub fn load(
+    path: impl AsRef<Path>,
+) -> Result<HashMap<String, Arc<dyn Material + Sync + Send>>, Error> {
+    let path = path.as_ref();
+    let mut lines = Lines::open(path)?;
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Parameters)> = None;
+
+    while let Some((keyword, arguments)) = lines.next_statement()? {
+        if keyword == "newmtl" {
+            let [name] = arguments.as_slice() else {
+                return Err(lines.error("expected a single material name"));
+            };
+            if let Some((name, parameters)) = current.take() {
+                materials.insert(name, parameters.into_material());
+            }
+            current = Some((name.clone(), Parameters::default()));
+            continue;
+        }
+
+        let Some((_, parameters)) = current.as_mut() else {
+            return Err(lines.error(format!("`{keyword}` before any `newmtl`")));
+        };
+        match keyword.as_str() {
+            "Kd" => parameters.diffuse = parse_color(&lines, &arguments)?,
+            "Ks" => parameters.specular = parse_color(&lines, &arguments)?,
+            "Ns" => {
+                [parameters.specular_exponent] = lines.parse_numbers::<1, 1>(&arguments, 0.0)?
+            }
+            "Ni" => [parameters.refraction_index] = lines.parse_numbers::<1, 1>(&arguments, 0.0)?,
+            "d" => [parameters.dissolve] = lines.parse_numbers::<1, 1>(&arguments, 0.0)?,
+            "Tr" => {
+                let [transparency] = lines.parse_numbers::<1, 1>(&arguments, 0.0)?;
+                parameters.dissolve = 1.0 - transparency;
+            }
+            "illum" => {
+                let [illumination] = arguments.as_slice() else {
+                    return Err(lines.error("expected a single illumination model"));
+                };
+                parameters.illumination = illumination.parse().map_err(|_| {
+                    lines.error(format!("invalid illumination model `{illumination}`"))
+                })?;
+            }
+            // Ambient and emissive colors, texture maps and other extensions are not supported
+            _ => {}
+        }
+    }
+    if let Some((name, parameters)) = current {
+        materials.insert(name, parameters.into_material());
+    }
+    Ok(materials)
+}
+
+/// Parses an RGB color, a single value is used for all channels
+fn parse_color(lines: &Lines, arguments: &[String]) -> Result<Color, Error> {
+    if arguments
+        .first()
+        .is_some_and(|argument| argument == "spectral" || argument == "xyz")
+    {
+        return Err(lines.error("only RGB colors are supported"));
+    }
+    let [r, g, b] = lines.parse_numbers::<1, 3>(arguments, f64::NAN)?;
+    Ok(if g.is_nan() {
+        Color::new([r; 3])
+    } else if b.is_nan() {
+        return Err(lines.error("expected 1 or 3 color components"));
+    } else {
+        Color::new([r, g, b])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        f64::consts::{FRAC_1_SQRT_2, PI},
+        fs,
+    };
+
+    use super::*;
+    use crate::{
+        material::{Scatter, testing::hit},
+        ray::Ray,
+        rng::reseed,
+        vec3::Vec3,
+    };
+
+    /// Writes the MTL text to a temporary file and loads its only material
+    fn load_material(name: &str, text: &str) -> Arc<dyn Material + Sync + Send> {
+        let path = std::env::temp_dir().join(format!("mtl_test_{name}_{}.mtl", std::process::id()));
+        fs::write(&path, format!("newmtl {name}\n{text}")).unwrap();
+        let materials = load(&path);
+        fs::remove_file(&path).unwrap();
+        materials.unwrap().remove(name).unwrap()
+    }
+
+    /// Scatters rays hitting the material from the front at 45°, returning the directions and
+    /// attenuations of those not absorbed
+    fn scatter(material: &Arc<dyn Material + Sync + Send>) -> Vec<(Vec3, Color)> {
+        let (ray, record) = hit(material.clone(), 45.0, true);
+        (0..100)
+            .filter_map(|_| match material.scatter(&ray, &record)? {
+                Scatter::Specular { ray, attenuation } => Some((*ray.direction(), attenuation)),
+                Scatter::Sampled { .. } => panic!("the material doesn't scatter specularly"),
+            })
+            .collect()
+    }
+
+    fn assert_lambertian(material: &Arc<dyn Material + Sync + Send>, albedo: Color) {
+        let (ray, record) = hit(material.clone(), 45.0, true);
+        assert!(matches!(
+            material.scatter(&ray, &record),
+            Some(Scatter::Sampled { .. })
+        ));
+        let normal = Ray::new(*record.point(), Vec3::new([0.0, 1.0, 0.0]), 0.0);
+        let scattering = material.scattering(&ray, &record, &normal);
+        assert!((scattering * PI - albedo).near_zero(), "{scattering:?}");
+    }
+
+    /// Checks the material reflects to directions at the fuzz distance from the mirror direction
+    fn assert_metal(material: &Arc<dyn Material + Sync + Send>, albedo: Color, fuzz: f64) {
+        reseed(61);
+        let mirrored = Vec3::new([FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0]);
+        let scattered = scatter(material);
+        assert!(scattered.len() > 50, "{}", scattered.len());
+        for (direction, attenuation) in scattered {
+            assert_eq!(attenuation, albedo);
+            let distance = (direction - mirrored).length();
+            assert!((distance - fuzz).abs() < 1e-9, "{distance}");
+        }
+    }
+
+    /// Checks the material refracts with the refractive index
+    fn assert_dielectric(material: &Arc<dyn Material + Sync + Send>, refraction_index: f64) {
+        reseed(62);
+        let refracted: Vec<_> = scatter(material)
+            .into_iter()
+            .inspect(|(_, attenuation)| assert_eq!(*attenuation, Color::new([1.0; 3])))
+            .filter(|(direction, _)| direction.y() < 0.0)
+            .collect();
+        assert!(refracted.len() > 50, "{}", refracted.len());
+        for (direction, _) in refracted {
+            // Snell's law, sin 45° = n sin θ
+            let index = FRAC_1_SQRT_2 / direction.x();
+            assert!((index - refraction_index).abs() < 1e-9, "{index}");
+        }
+    }
+
+    #[test]
+    fn diffuse_models_are_lambertian() {
+        assert_lambertian(&load_material("default", ""), Color::new([0.8; 3]));
+        for illumination in [0, 1, 2] {
+            let material = load_material(
+                "diffuse",
+                &format!("Kd 0.1 0.2 0.3\nKs 1\nNs 50\nd 1\nillum {illumination}\n"),
+            );
+            assert_lambertian(&material, Color::new([0.1, 0.2, 0.3]));
+        }
+    }
+
+    #[test]
+    fn reflective_models_are_metal_with_fuzz_from_the_exponent() {
+        let cases = [
+            (3, 98.0, 0.02_f64.sqrt()),
+            (5, 0.0, 1.0),
+            (8, 2.0, FRAC_1_SQRT_2),
+        ];
+        for (illumination, exponent, fuzz) in cases {
+            let material = load_material(
+                "metal",
+                &format!("Kd 0.1\nKs 0.9 0.8 0.7\nNs {exponent}\nillum {illumination}\n"),
+            );
+            assert_metal(&material, Color::new([0.9, 0.8, 0.7]), fuzz);
+        }
+    }
+
+    #[test]
+    fn transparent_models_are_dielectric() {
+        for illumination in [4, 6, 7, 9] {
+            let material =
+                load_material("glass", &format!("Ni 1.5\nNs 90\nillum {illumination}\n"));
+            assert_dielectric(&material, 1.5);
+        }
+    }
+
+    #[test]
+    fn dissolved_materials_are_dielectric() {
+        for text in ["d 0.5\nillum 2\n", "Tr 0.25\n", "d 0.9\nNs 90\nillum 3\n"] {
+            assert_dielectric(
+                &load_material("dissolved", &format!("Ni 1.33\n{text}")),
+                1.33,
+            );
+        }
+        // Without an index the surface is thin and doesn't bend rays
+        assert_dielectric(&load_material("thin", "d 0.5\n"), 1.0);
+    }
+}
@@ -0,0 +1,47 @@
+use crate::vec3::Vec3;
+
+/// Orthonormal basis around a direction, which becomes the local z axis
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    axis: [Vec3; 3],
+}
+
+impl Onb {
+    pub fn new(normal: &Vec3) -> Self {
+        let w = normal.unit_vector();
+        let helper = if w.x().abs() > 0.9 {
+            Vec3::new([0.0, 1.0, 0.0])
+        } else {
+            Vec3::new([1.0, 0.0, 0.0])
+        };
+        let v = w.cross(&helper).unit_vector();
+        let u = w.cross(&v);
+        Self { axis: [u, v, w] }
+    }
+
+    pub const fn u(&self) -> &Vec3 {
+        &self.axis[0]
+    }
+
+    pub const fn v(&self) -> &Vec3 {
+        &self.axis[1]
+    }
+
+    pub const fn w(&self) -> &Vec3 {
+        &self.axis[2]
+    }
+
+    /// Transforms a vector from world coordinates into basis coordinates
+    pub const fn to_local(&self, world: &Vec3) -> Vec3 {
+        Vec3::new([
+            world.dot(&self.axis[0]),
+            world.dot(&self.axis[1]),
+            world.dot(&self.axis[2]),
+        ])
+    }
+
+    /// Transforms a vector from basis coordinates into world coordinates
+    pub fn transform(&self, local: &Vec3) -> Vec3 {
+        local.x() * self.axis[0] + local.y() * self.axis[1] + local.z() * self.axis[2]
+    }
+}
@@ -0,0 +1,178 @@
+//! Writers encoding a [`Framebuffer`] to image files
+
+use std::{
+    error,
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufWriter, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use image::{ImageError, ImageFormat};
+
+use crate::{framebuffer::Framebuffer, tone_map::ToneMapping};
+
+#[derive(Debug)]
+pub enum Error {
+    /// The output file could not be created or written
+    Io { path: PathBuf, source: io::Error },
+
+    /// The image could not be encoded
+    Encode { path: PathBuf, source: ImageError },
+
+    /// The file extension doesn't match any supported format
+    UnsupportedFormat { path: PathBuf },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Encode { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::UnsupportedFormat { path } => {
+                write!(f, "{}: unsupported image format", path.display())
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Encode { source, .. } => Some(source),
+            Self::UnsupportedFormat { .. } => None,
+        }
+    }
+}
+
+/// Writes the framebuffer to `path`, in the format matching the file extension. OpenEXR
+/// (`.exr`) and Radiance (`.hdr`) files store the linear colors, other formats are 8-bit after
+/// tone mapping.
+pub fn save(
+    framebuffer: &Framebuffer,
+    path: impl AsRef<Path>,
+    tone_mapping: &ToneMapping,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let format = ImageFormat::from_path(path)
+        .ok()
+        .filter(|format| format.writing_enabled())
+        .ok_or_else(|| Error::UnsupportedFormat {
+            path: path.to_owned(),
+        })?;
+    let io_error = |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    };
+
+    let mut writer = BufWriter::new(File::create(path).map_err(io_error)?);
+    match format {
+        ImageFormat::OpenExr | ImageFormat::Hdr => write_hdr(framebuffer, &mut writer, format),
+        _ => write_ldr(framebuffer, &mut writer, format, tone_mapping),
+    }
+    .map_err(|source| Error::Encode {
+        path: path.to_owned(),
+        source,
+    })?;
+    writer.flush().map_err(io_error)
+}
+
+/// Encodes the framebuffer as linear floating point colors, for formats supporting them
+pub fn write_hdr(
+    framebuffer: &Framebuffer,
+    writer: &mut (impl Write + Seek),
+    format: ImageFormat,
+) -> Result<(), ImageError> {
+    framebuffer.to_rgb32f().write_to(writer, format)
+}
+
+/// Encodes the framebuffer as a tone mapped 8-bit sRGB image
+pub fn write_ldr(
+    framebuffer: &Framebuffer,
+    writer: &mut (impl Write + Seek),
+    format: ImageFormat,
+    tone_mapping: &ToneMapping,
+) -> Result<(), ImageError> {
+    framebuffer.to_rgb8(tone_mapping).write_to(writer, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::color::Color;
+
+    /// Framebuffer with values above 1, which only float formats keep
+    fn framebuffer() -> Framebuffer {
+        Framebuffer::new(
+            2,
+            2,
+            vec![
+                Color::new([0.25, 0.5, 0.75]),
+                Color::new([1.5, 3.0, 6.0]),
+                Color::new([0.0, 0.0, 0.0]),
+                Color::new([40.0, 0.125, 2.0]),
+            ],
+        )
+    }
+
+    fn temp_path(extension: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("output_test_{}.{extension}", std::process::id()))
+    }
+
+    /// Saves the framebuffer and reads it back as linear colors
+    fn round_trip(extension: &str) -> Vec<Color> {
+        let path = temp_path(extension);
+        let saved = save(&framebuffer(), &path, &ToneMapping::default());
+        let image = image::open(&path);
+        fs::remove_file(&path).unwrap();
+        saved.unwrap();
+        let image = image.unwrap().into_rgb32f();
+        assert_eq!(image.dimensions(), (2, 2));
+        image
+            .pixels()
+            .map(|pixel| Color::new(pixel.0.map(f64::from)))
+            .collect()
+    }
+
+    #[test]
+    fn exr_keeps_the_linear_colors() {
+        assert_eq!(round_trip("exr"), framebuffer().pixels());
+    }
+
+    #[test]
+    fn hdr_keeps_the_linear_colors() {
+        // The shared exponent keeps about 8 bits of precision relative to the largest channel
+        for (read, written) in round_trip("hdr").iter().zip(framebuffer().pixels()) {
+            let tolerance = written.x().max(written.y()).max(written.z()) / 128.0;
+            for axis in 0..3 {
+                assert!(
+                    (read[axis] - written[axis]).abs() <= tolerance,
+                    "{read:?} {written:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_extensions_are_rejected() {
+        let path = temp_path("xyz");
+        let result = save(&framebuffer(), &path, &ToneMapping::default());
+        assert!(
+            matches!(result, Err(Error::UnsupportedFormat { .. })),
+            "{result:?}"
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn io_failures_are_reported() {
+        let path = std::env::temp_dir()
+            .join(format!("output_test_missing_{}", std::process::id()))
+            .join("image.exr");
+        let result = save(&framebuffer(), &path, &ToneMapping::default());
+        assert!(matches!(result, Err(Error::Io { .. })), "{result:?}");
+    }
+}
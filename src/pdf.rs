@@ -0,0 +1,92 @@
+//! Probability density functions over directions, for importance sampling
+
+use crate::vec3::Vec3;
+
+pub mod cosine;
+pub mod ggx_dielectric;
+pub mod ggx_reflection;
+pub mod hittable_pdf;
+pub mod mixture;
+pub mod sphere;
+
+pub trait Pdf {
+    /// Returns the density of the direction, per unit solid angle
+    fn value(&self, direction: &Vec3) -> f64;
+
+    /// Returns a random direction distributed by the density
+    fn generate(&self) -> Vec3;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use super::*;
+    use crate::{
+        color::Color,
+        hittable::{Hittable, quad::Quad, sphere::Sphere},
+        material::lambertian::Lambertian,
+        pdf::{
+            cosine::CosinePdf,
+            hittable_pdf::HittablePdf,
+            mixture::{MixturePdf, WeightedMixturePdf},
+            sphere::SpherePdf,
+        },
+        rng::reseed,
+        vec3::Point3,
+    };
+
+    /// Estimates the solid angle a density is spread over, as the mean of its inverse at its
+    /// own samples
+    fn solid_angle(pdf: &dyn Pdf) -> f64 {
+        const SAMPLES: usize = 20_000;
+        (0..SAMPLES)
+            .map(|_| 1.0 / pdf.value(&pdf.generate()))
+            .sum::<f64>()
+            / SAMPLES as f64
+    }
+
+    fn gray() -> Arc<Lambertian> {
+        Arc::new(Lambertian::new(Color::new([0.5; 3])))
+    }
+
+    #[test]
+    fn densities_cover_their_solid_angle() {
+        reseed(5);
+        let normal = Vec3::new([0.0, 1.0, 1.0]);
+        assert!((solid_angle(&CosinePdf::new(&normal)) - 2.0 * PI).abs() < 0.2);
+        assert!((solid_angle(&SpherePdf) - 4.0 * PI).abs() < 1e-9);
+        assert!(
+            (solid_angle(&MixturePdf::new(&SpherePdf, &CosinePdf::new(&normal))) - 4.0 * PI).abs()
+                < 0.3
+        );
+        let weighted = WeightedMixturePdf::new([
+            (3.0, Box::new(CosinePdf::new(&normal)) as Box<dyn Pdf>),
+            (1.0, Box::new(SpherePdf)),
+            (0.0, Box::new(SpherePdf)),
+        ]);
+        assert!((solid_angle(&weighted) - 4.0 * PI).abs() < 0.3);
+    }
+
+    #[test]
+    fn light_densities_match_solid_angle() {
+        reseed(11);
+        let origin = Point3::new([0.0; 3]);
+
+        // Unit sphere at distance 2 subtends a cone with cos θ = √3 / 2
+        let sphere: &(dyn Hittable + Sync + Send) =
+            &Sphere::new(Point3::new([0.0, 0.0, 2.0]), 1.0, gray());
+        let expected = 2.0 * PI * (1.0 - 3.0_f64.sqrt() / 2.0);
+        assert!((solid_angle(&HittablePdf::new(sphere, origin)) - expected).abs() < 0.01);
+
+        // Square of side 2 at distance 1 subtends 4 asin(1 / 2)
+        let quad: &(dyn Hittable + Sync + Send) = &Quad::new(
+            Point3::new([-1.0, -1.0, 1.0]),
+            Vec3::new([2.0, 0.0, 0.0]),
+            Vec3::new([0.0, 2.0, 0.0]),
+            gray(),
+        );
+        let expected = 4.0 * 0.5_f64.asin();
+        assert!((solid_angle(&HittablePdf::new(quad, origin)) - expected).abs() < 0.05);
+    }
+}
@@ -0,0 +1,27 @@
+use std::f64::consts::PI;
+
+use crate::{onb::Onb, pdf::Pdf, vec3::Vec3};
+
+/// Cosine-weighted hemisphere around a normal, matching Lambertian reflection
+pub struct CosinePdf {
+    basis: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(normal: &Vec3) -> Self {
+        Self {
+            basis: Onb::new(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        let cosine = direction.unit_vector().dot(self.basis.w());
+        (cosine / PI).max(0.0)
+    }
+
+    fn generate(&self) -> Vec3 {
+        self.basis.transform(&Vec3::random_cosine_direction())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{
+    material::dielectric::fresnel_dielectric,
+    microfacet::{Ggx, scattering_normal},
+    onb::Onb,
+    pdf::Pdf,
+    rng::random,
+    vec3::Vec3,
+};
+
+/// Reflections off and transmissions through GGX microfacets of a dielectric interface,
+/// choosing between them by the Fresnel reflectance of the visible normal sampled
+pub struct GgxDielectricPdf {
+    basis: Onb,
+
+    /// Unit direction back along the incoming ray, in basis coordinates
+    outgoing: Vec3,
+
+    /// Refractive index beyond the interface over the index on the side of `outgoing`
+    eta: f64,
+
+    ggx: Ggx,
+}
+
+impl GgxDielectricPdf {
+    pub const fn new(basis: Onb, outgoing: Vec3, eta: f64, ggx: Ggx) -> Self {
+        Self {
+            basis,
+            outgoing,
+            eta,
+            ggx,
+        }
+    }
+}
+
+impl Pdf for GgxDielectricPdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        let incoming = self.basis.to_local(&direction.unit_vector());
+        let Some(normal) = scattering_normal(&self.outgoing, &incoming, self.eta) else {
+            return 0.0;
+        };
+        let outgoing_dot_normal = self.outgoing.dot(&normal);
+        let reflectance = fresnel_dielectric(outgoing_dot_normal, self.eta);
+        let normal_pdf = self.ggx.visible_normal_pdf(&self.outgoing, &normal);
+        if incoming.z() > 0.0 {
+            normal_pdf * reflectance / (4.0 * outgoing_dot_normal)
+        } else {
+            // Jacobian of refracting the outgoing direction through the microfacet
+            let incoming_dot_normal = incoming.dot(&normal);
+            let denominator = outgoing_dot_normal + self.eta * incoming_dot_normal;
+            normal_pdf * (1.0 - reflectance) * self.eta * self.eta * incoming_dot_normal.abs()
+                / (denominator * denominator)
+        }
+    }
+
+    fn generate(&self) -> Vec3 {
+        let normal = self.ggx.sample_visible_normal(&self.outgoing);
+        let cos_outgoing = self.outgoing.dot(&normal);
+        let incoming = if random::<f64>() < fresnel_dielectric(cos_outgoing, self.eta) {
+            2.0 * cos_outgoing * normal - self.outgoing
+        } else {
+            let sin_squared_transmitted =
+                (1.0 - cos_outgoing * cos_outgoing) / (self.eta * self.eta);
+            let cos_transmitted = (1.0 - sin_squared_transmitted).sqrt();
+            -self.outgoing / self.eta + (cos_outgoing / self.eta - cos_transmitted) * normal
+        };
+        self.basis.transform(&incoming)
+    }
+}
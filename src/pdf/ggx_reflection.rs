@@ -0,0 +1,44 @@
+use crate::{microfacet::Ggx, onb::Onb, pdf::Pdf, vec3::Vec3};
+
+/// Reflections off GGX microfacets, sampled from the normals visible from the outgoing
+/// direction
+pub struct GgxReflectionPdf {
+    basis: Onb,
+
+    /// Unit direction back along the incoming ray, in basis coordinates
+    outgoing: Vec3,
+
+    ggx: Ggx,
+}
+
+impl GgxReflectionPdf {
+    pub const fn new(basis: Onb, outgoing: Vec3, ggx: Ggx) -> Self {
+        Self {
+            basis,
+            outgoing,
+            ggx,
+        }
+    }
+}
+
+impl Pdf for GgxReflectionPdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        let incoming = self.basis.to_local(&direction.unit_vector());
+        if incoming.z() <= 0.0 {
+            return 0.0;
+        }
+        let half_vector = (self.outgoing + incoming).unit_vector();
+        let outgoing_dot_half = self.outgoing.dot(&half_vector);
+        if outgoing_dot_half <= 0.0 {
+            return 0.0;
+        }
+        // Jacobian of reflecting the outgoing direction around the microfacet normal
+        self.ggx.visible_normal_pdf(&self.outgoing, &half_vector) / (4.0 * outgoing_dot_half)
+    }
+
+    fn generate(&self) -> Vec3 {
+        let normal = self.ggx.sample_visible_normal(&self.outgoing);
+        let incoming = 2.0 * self.outgoing.dot(&normal) * normal - self.outgoing;
+        self.basis.transform(&incoming)
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{
+    hittable::Hittable,
+    pdf::Pdf,
+    vec3::{Point3, Vec3},
+};
+
+/// Directions from a point toward an object, such as a light
+pub struct HittablePdf<'a> {
+    object: &'a (dyn Hittable + Sync + Send),
+    origin: Point3,
+}
+
+impl<'a> HittablePdf<'a> {
+    pub const fn new(object: &'a (dyn Hittable + Sync + Send), origin: Point3) -> Self {
+        Self { object, origin }
+    }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn value(&self, direction: &Vec3) -> f64 {
+        self.object.pdf_value(&self.origin, direction)
+    }
+
+    fn generate(&self) -> Vec3 {
+        self.object.random(&self.origin)
+    }
+}
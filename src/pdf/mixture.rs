@@ -0,0 +1,74 @@
+use crate::{pdf::Pdf, rng::random, vec3::Vec3};
+
+/// Equal mix of two densities, sampling either one with the same probability
+pub struct MixturePdf<'a> {
+    pdfs: [&'a dyn Pdf; 2],
+}
+
+impl<'a> MixturePdf<'a> {
+    pub const fn new(first: &'a dyn Pdf, second: &'a dyn Pdf) -> Self {
+        Self {
+            pdfs: [first, second],
+        }
+    }
+}
+
+impl Pdf for MixturePdf<'_> {
+    fn value(&self, direction: &Vec3) -> f64 {
+        0.5 * self.pdfs[0].value(direction) + 0.5 * self.pdfs[1].value(direction)
+    }
+
+    fn generate(&self) -> Vec3 {
+        if random::<f64>() < 0.5 {
+            self.pdfs[0].generate()
+        } else {
+            self.pdfs[1].generate()
+        }
+    }
+}
+
+/// Mix of densities with weights, sampling each one with the probability of its weight
+pub struct WeightedMixturePdf {
+    /// Densities with their probabilities, which sum to one
+    pdfs: Vec<(f64, Box<dyn Pdf>)>,
+}
+
+impl WeightedMixturePdf {
+    /// Mixes the densities with weights normalized to sum to one, skipping the ones without
+    /// weight. At least one weight must be positive.
+    pub fn new(pdfs: impl IntoIterator<Item = (f64, Box<dyn Pdf>)>) -> Self {
+        let pdfs: Vec<_> = pdfs
+            .into_iter()
+            .filter(|(weight, _)| *weight > 0.0)
+            .collect();
+        let total: f64 = pdfs.iter().map(|(weight, _)| weight).sum();
+        assert!(total > 0.0, "mixtures need a density with positive weight");
+        Self {
+            pdfs: pdfs
+                .into_iter()
+                .map(|(weight, pdf)| (weight / total, pdf))
+                .collect(),
+        }
+    }
+}
+
+impl Pdf for WeightedMixturePdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        self.pdfs
+            .iter()
+            .map(|(probability, pdf)| probability * pdf.value(direction))
+            .sum()
+    }
+
+    fn generate(&self) -> Vec3 {
+        let mut choice = random::<f64>();
+        for (probability, pdf) in &self.pdfs {
+            if choice < *probability {
+                return pdf.generate();
+            }
+            choice -= probability;
+        }
+        // Rounding left the choice beyond the last probability
+        self.pdfs[self.pdfs.len() - 1].1.generate()
+    }
+}
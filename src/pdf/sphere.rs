@@ -0,0 +1,17 @@
+use std::f64::consts::PI;
+
+use crate::{pdf::Pdf, vec3::Vec3};
+
+/// Uniform density over all directions
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: &Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn generate(&self) -> Vec3 {
+        Vec3::random_unit_vector()
+    }
+}
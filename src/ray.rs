@@ -1,14 +1,35 @@
-use crate::vec3::{Point3, Vec3};
+use crate::{
+    spectrum::Wavelengths,
+    vec3::{Point3, Vec3},
+};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Ray {
     origin: Point3,
     direction: Vec3,
+
+    /// Moment within the camera shutter interval the ray exists at, used for motion blur
+    shutter_time: f64,
+
+    /// Wavelengths the ray carries in spectral rendering
+    wavelengths: Option<Wavelengths>,
 }
 
 impl Ray {
-    pub const fn new(origin: Point3, direction: Vec3) -> Self {
-        Self { origin, direction }
+    pub const fn new(origin: Point3, direction: Vec3, shutter_time: f64) -> Self {
+        Self {
+            origin,
+            direction,
+            shutter_time,
+            wavelengths: None,
+        }
+    }
+
+    pub const fn with_wavelengths(self, wavelengths: Option<Wavelengths>) -> Self {
+        Self {
+            wavelengths,
+            ..self
+        }
     }
 
     pub const fn origin(&self) -> &Point3 {
@@ -19,6 +40,14 @@ impl Ray {
         &self.direction
     }
 
+    pub const fn shutter_time(&self) -> f64 {
+        self.shutter_time
+    }
+
+    pub const fn wavelengths(&self) -> Option<Wavelengths> {
+        self.wavelengths
+    }
+
     pub fn at(&self, time: f64) -> Point3 {
         self.origin + time * self.direction
     }
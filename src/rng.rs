@@ -0,0 +1,41 @@
+//! Thread-local random number generation that can be reseeded, so renders are reproducible
+
+use std::cell::RefCell;
+
+use rand::{
+    Rng, SeedableRng,
+    distr::{Distribution, StandardUniform, uniform::SampleRange, uniform::SampleUniform},
+    rngs::StdRng,
+};
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_os_rng());
+}
+
+/// Reseeds the generator of the current thread
+pub fn reseed(seed: u64) {
+    RNG.with_borrow_mut(|rng| *rng = StdRng::seed_from_u64(seed));
+}
+
+/// Mixes several values into a single well distributed seed (SplitMix64 finalizer)
+pub fn mix_seed(values: impl IntoIterator<Item = u64>) -> u64 {
+    values
+        .into_iter()
+        .fold(0x9e37_79b9_7f4a_7c15, |hash, value| {
+            let mut z = (hash ^ value).wrapping_add(0x9e37_79b9_7f4a_7c15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        })
+}
+
+pub fn random<T>() -> T
+where
+    StandardUniform: Distribution<T>,
+{
+    RNG.with_borrow_mut(|rng| rng.random())
+}
+
+pub fn random_range<T: SampleUniform, R: SampleRange<T>>(range: R) -> T {
+    RNG.with_borrow_mut(|rng| rng.random_range(range))
+}
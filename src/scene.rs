@@ -0,0 +1,1505 @@
+//! Declarative TOML scene description, describing the camera, named materials and the objects
+//! using them. Named geometries are shared by every instance placing them in the scene.
+//!
+//! ```toml
+//! [camera]
+//! image_width = 400
+//! look_from = [13, 2, 3]
+//!
+//! [background]
+//! type = "gradient"
+//! bottom = [1, 1, 1]
+//! top = [0.5, 0.7, 1]
+//!
+//! [textures.checker]
+//! type = "checker"
+//! scale = 0.32
+//! even = [0.2, 0.3, 0.1]
+//! odd = [0.9, 0.9, 0.9]
+//!
+//! [materials.ground]
+//! type = "lambertian"
+//! albedo = "checker"
+//!
+//! [[objects]]
+//! type = "sphere"
+//! center = [0, -1000, 0]
+//! radius = 1000
+//! material = "ground"
+//!
+//! [geometries.pillar]
+//! type = "box"
+//! a = [-0.5, 0, -0.5]
+//! b = [0.5, 3, 0.5]
+//! material = "ground"
+//!
+//! [[objects]]
+//! type = "instance"
+//! geometry = "pillar"
+//! translate = [4, 0, 0]
+//! rotate = [0, 45, 0]
+//! scale = 0.5
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error,
+    fmt::{self, Display},
+    fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use image::ImageError;
+use serde::Deserialize;
+
+use crate::{
+    background::{Background, EnvironmentMap},
+    camera::Camera,
+    color::Color,
+    hittable::{
+        Hittable, bvh::BvhNode, constant_medium::ConstantMedium, cuboid::Cuboid, disk::Disk,
+        heterogeneous_medium::HeterogeneousMedium, list::List, moving_sphere::MovingSphere,
+        plane::Plane, quad::Quad, sphere::Sphere, transform::Instance, triangle::Triangle,
+    },
+    interval::Interval,
+    material::{
+        Material,
+        conductor::{ComplexIor, Conductor},
+        dielectric::{Dielectric, Dispersion, REFERENCE_WAVELENGTH, absorption_from_color},
+        diffuse_light::DiffuseLight,
+        isotropic::Isotropic,
+        lambertian::Lambertian,
+        metal::Metal,
+        principled::Principled,
+        rough_dielectric::RoughDielectric,
+    },
+    matrix::Matrix4,
+    obj,
+    texture::{
+        Texture,
+        checker::Checker,
+        image_texture::ImageTexture,
+        noise::{NoiseTexture, Pattern},
+        solid_color::SolidColor,
+    },
+    vec3::{Point3, Vec3},
+    voxel_grid::{self, VoxelGrid},
+};
+
+#[derive(Debug)]
+pub enum Error {
+    /// The scene file could not be read
+    Io { path: PathBuf, source: io::Error },
+
+    /// The scene file is not valid TOML or doesn't match the scene structure
+    Syntax {
+        path: PathBuf,
+        source: Box<toml::de::Error>,
+    },
+
+    /// The scene is well-formed but describes something impossible
+    Invalid { path: PathBuf, message: String },
+
+    /// A mesh referenced by the scene could not be loaded
+    Mesh(obj::Error),
+
+    /// A voxel grid file referenced by the scene could not be loaded
+    Volume(voxel_grid::Error),
+
+    /// An image referenced by the scene could not be loaded
+    Image { path: PathBuf, source: ImageError },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Syntax { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Invalid { path, message } => write!(f, "{}: {message}", path.display()),
+            Self::Mesh(error) => error.fmt(f),
+            Self::Volume(error) => error.fmt(f),
+            Self::Image { path, source } => write!(f, "{}: {source}", path.display()),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Syntax { source, .. } => Some(source),
+            Self::Invalid { .. } => None,
+            Self::Mesh(error) => Some(error),
+            Self::Volume(error) => Some(error),
+            Self::Image { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<obj::Error> for Error {
+    fn from(value: obj::Error) -> Self {
+        Self::Mesh(value)
+    }
+}
+
+impl From<voxel_grid::Error> for Error {
+    fn from(value: voxel_grid::Error) -> Self {
+        Self::Volume(value)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CameraDescription {
+    aspect_ratio: f64,
+    image_width: u32,
+    samples_per_pixel: u16,
+    max_depth: u8,
+    /// Number of bounces after which paths may be terminated by Russian roulette
+    roulette_depth: u8,
+    vfov: f64,
+    look_from: [f64; 3],
+    look_at: [f64; 3],
+    relative_up: [f64; 3],
+    defocus_angle: f64,
+    focus_distance: f64,
+    shutter: [f64; 2],
+    /// Whether to render with sampled wavelengths, needed for dispersion
+    spectral: bool,
+}
+
+impl Default for CameraDescription {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: 16.0 / 9.0,
+            image_width: 400,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            roulette_depth: 3,
+            vfov: 90.0,
+            look_from: [0.0; 3],
+            look_at: [0.0, 0.0, -1.0],
+            relative_up: [0.0, 1.0, 0.0],
+            defocus_angle: 0.0,
+            focus_distance: 10.0,
+            shutter: [0.0, 1.0],
+            spectral: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDescription {
+    Solid {
+        color: [f64; 3],
+    },
+    Checker {
+        scale: f64,
+        even: [f64; 3],
+        odd: [f64; 3],
+    },
+    /// Image file, with a path relative to the scene file
+    Image {
+        path: PathBuf,
+    },
+    /// Perlin noise, see [`NoiseTexture`]
+    Noise {
+        #[serde(default)]
+        pattern: PatternDescription,
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        #[serde(default = "default_noise_octaves")]
+        octaves: u32,
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_noise_color")]
+        color: [f64; 3],
+    },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PatternDescription {
+    Smooth,
+    Turbulence,
+    #[default]
+    Marble,
+}
+
+const fn default_noise_scale() -> f64 {
+    1.0
+}
+
+const fn default_noise_octaves() -> u32 {
+    7
+}
+
+const fn default_noise_color() -> [f64; 3] {
+    [1.0; 3]
+}
+
+/// Either a constant color or the name of a texture
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AlbedoDescription {
+    Color([f64; 3]),
+    Texture(String),
+}
+
+/// Either a constant value or the name of a texture, whose channels are averaged
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ParameterDescription {
+    Value(f64),
+    Texture(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDescription {
+    Lambertian {
+        albedo: AlbedoDescription,
+    },
+    Metal {
+        albedo: AlbedoDescription,
+        fuzz: f64,
+    },
+    /// Glass, frosted by microfacets if it has a roughness. Colored glass absorbs light
+    /// travelling through it, either by an absorption coefficient per unit of distance or by
+    /// the color of white light after travelling `distance`. Absorption is only applied where
+    /// a ray leaves the glass it entered, so objects inside colored glass, or glass touching
+    /// other glass, cut the absorbed distance short. Smooth glass may have a dispersion
+    /// instead of a refractive index, splitting light into colors when rendering spectrally.
+    Dielectric {
+        refraction_index: Option<f64>,
+        dispersion: Option<DispersionDescription>,
+        #[serde(default)]
+        roughness: f64,
+        absorption: Option<[f64; 3]>,
+        color: Option<[f64; 3]>,
+        #[serde(default = "default_color_distance")]
+        distance: f64,
+    },
+    /// Microfacet metal, from a preset or a complex refractive index per color channel
+    Conductor {
+        metal: Option<MetalPreset>,
+        eta: Option<[f64; 3]>,
+        k: Option<[f64; 3]>,
+        #[serde(default)]
+        roughness: f64,
+    },
+    /// Uber material, see [`Principled`] for the parameters and their defaults
+    Principled {
+        base_color: AlbedoDescription,
+        metallic: Option<ParameterDescription>,
+        roughness: Option<ParameterDescription>,
+        specular: Option<ParameterDescription>,
+        specular_tint: Option<ParameterDescription>,
+        sheen: Option<ParameterDescription>,
+        clearcoat: Option<ParameterDescription>,
+        transmission: Option<ParameterDescription>,
+        refraction_index: Option<ParameterDescription>,
+    },
+    DiffuseLight {
+        emit: [f64; 3],
+    },
+    /// Phase function of volumes, scattering equally in all directions
+    Isotropic {
+        albedo: AlbedoDescription,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum DispersionDescription {
+    Bk7,
+    Diamond,
+    /// Cauchy's equation, see [`Dispersion::Cauchy`]
+    Cauchy {
+        a: f64,
+        b: f64,
+    },
+    /// Sellmeier equation, see [`Dispersion::Sellmeier`]
+    Sellmeier {
+        b: [f64; 3],
+        c: [f64; 3],
+    },
+}
+
+const fn default_color_distance() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MetalPreset {
+    Gold,
+    Copper,
+    Aluminium,
+    Silver,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyframeDescription {
+    time: f64,
+    center: [f64; 3],
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDescription {
+    Sphere {
+        center: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// Sphere moving along a path of keyframes during the camera shutter interval
+    MovingSphere {
+        keyframes: Vec<KeyframeDescription>,
+        radius: f64,
+        material: String,
+    },
+    Triangle {
+        vertices: [[f64; 3]; 3],
+        material: String,
+    },
+    /// Parallelogram spanned by the edges `u` and `v` from `corner`
+    Quad {
+        corner: [f64; 3],
+        u: [f64; 3],
+        v: [f64; 3],
+        material: String,
+    },
+    /// Infinite plane through `point`
+    Plane {
+        point: [f64; 3],
+        normal: [f64; 3],
+        material: String,
+    },
+    Disk {
+        center: [f64; 3],
+        normal: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// Axis-aligned box between two opposite corners
+    Box {
+        a: [f64; 3],
+        b: [f64; 3],
+        material: String,
+    },
+    /// Wavefront OBJ file, with a path relative to the scene file
+    Mesh { path: PathBuf },
+    /// Fog or smoke of constant density filling a closed boundary object, scattering with the
+    /// material as phase function
+    ConstantMedium {
+        boundary: Box<ObjectDescription>,
+        density: f64,
+        material: String,
+    },
+    /// Volume with densities from a voxel grid file, stretched over the axis-aligned box between
+    /// two opposite corners and multiplied by `density`
+    Volume {
+        path: PathBuf,
+        #[serde(default)]
+        format: GridFormat,
+        a: [f64; 3],
+        b: [f64; 3],
+        #[serde(default = "default_density")]
+        density: f64,
+        material: String,
+    },
+    /// Placement of a named geometry, scaled, then rotated around x, y and z in that order by
+    /// the given degrees, then translated
+    Instance {
+        geometry: String,
+        material: Option<String>,
+        #[serde(default)]
+        translate: [f64; 3],
+        #[serde(default)]
+        rotate: [f64; 3],
+        #[serde(default)]
+        scale: ScaleDescription,
+    },
+}
+
+impl ObjectDescription {
+    /// Returns the material of the object, or of the geometry placed by an instance without
+    /// its own material
+    fn material<'a>(&'a self, geometries: &'a BTreeMap<String, Self>) -> Option<&'a str> {
+        match self {
+            Self::Sphere { material, .. }
+            | Self::MovingSphere { material, .. }
+            | Self::Triangle { material, .. }
+            | Self::Quad { material, .. }
+            | Self::Plane { material, .. }
+            | Self::Disk { material, .. }
+            | Self::Box { material, .. }
+            | Self::ConstantMedium { material, .. }
+            | Self::Volume { material, .. } => Some(material),
+            Self::Mesh { .. } => None,
+            Self::Instance {
+                geometry, material, ..
+            } => material
+                .as_deref()
+                .or_else(|| geometries.get(geometry)?.material(geometries)),
+        }
+    }
+
+    /// Returns whether directions toward the object can be sampled, which lights need
+    fn can_be_sampled(&self, geometries: &BTreeMap<String, Self>) -> bool {
+        match self {
+            Self::Sphere { .. }
+            | Self::Triangle { .. }
+            | Self::Quad { .. }
+            | Self::Disk { .. }
+            | Self::Box { .. } => true,
+            Self::Instance { geometry, .. } => geometries
+                .get(geometry)
+                .is_some_and(|geometry| geometry.can_be_sampled(geometries)),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum GridFormat {
+    /// Dense grid of raw densities
+    #[default]
+    Raw,
+    /// Blocks of 8×8×8 voxels, like NanoVDB leaves
+    Sparse,
+}
+
+const fn default_density() -> f64 {
+    1.0
+}
+
+/// Either a uniform scale factor or one factor per axis
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum ScaleDescription {
+    Uniform(f64),
+    PerAxis([f64; 3]),
+}
+
+impl Default for ScaleDescription {
+    fn default() -> Self {
+        Self::Uniform(1.0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum BackgroundDescription {
+    Solid {
+        color: [f64; 3],
+    },
+    Gradient {
+        bottom: [f64; 3],
+        top: [f64; 3],
+    },
+    /// Equirectangular image, with a path relative to the scene file
+    Environment {
+        path: PathBuf,
+        #[serde(default)]
+        rotation: f64,
+        #[serde(default = "default_intensity")]
+        intensity: f64,
+    },
+}
+
+const fn default_intensity() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDescription {
+    #[serde(default)]
+    camera: CameraDescription,
+    background: Option<BackgroundDescription>,
+    #[serde(default)]
+    textures: BTreeMap<String, TextureDescription>,
+    #[serde(default)]
+    materials: BTreeMap<String, MaterialDescription>,
+    /// Named objects, placed in the scene by instances
+    #[serde(default)]
+    geometries: BTreeMap<String, ObjectDescription>,
+    #[serde(default)]
+    objects: Vec<ObjectDescription>,
+}
+
+/// Everything needed to render an image
+pub struct Scene {
+    pub camera: Camera,
+    pub world: BvhNode,
+
+    /// The emitting objects, shared with the world, sampled directly when scattering diffusely
+    pub lights: List,
+}
+
+impl Scene {
+    /// Loads and validates a TOML scene description
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::parse(path, &text)
+    }
+
+    /// Parses and validates the text of a scene description, resolving the files it refers to
+    /// relative to `path`
+    fn parse(path: &Path, text: &str) -> Result<Self, Error> {
+        let description: SceneDescription =
+            toml::from_str(text).map_err(|source| Error::Syntax {
+                path: path.to_owned(),
+                source: Box::new(source),
+            })?;
+        let invalid = |message: String| Error::Invalid {
+            path: path.to_owned(),
+            message,
+        };
+
+        let mut camera = description.camera.build().map_err(invalid)?;
+        if let Some(background) = description.background {
+            camera = camera.with_background(background.build(path)?);
+        }
+
+        let textures = description
+            .textures
+            .into_iter()
+            .map(|(name, texture)| {
+                let built = texture.build(&name, path)?;
+                Ok((name, built))
+            })
+            .collect::<Result<BTreeMap<_, _>, Error>>()?;
+
+        let emissive: BTreeSet<_> = description
+            .materials
+            .iter()
+            .filter(|(_, material)| matches!(material, MaterialDescription::DiffuseLight { .. }))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let materials = description
+            .materials
+            .into_iter()
+            .map(|(name, material)| {
+                let built = material
+                    .build(&textures)
+                    .map_err(|message| invalid(format!("material `{name}`: {message}")))?;
+                Ok((name, built))
+            })
+            .collect::<Result<BTreeMap<_, _>, Error>>()?;
+        let mut builder = ObjectBuilder {
+            scene_path: path,
+            materials: &materials,
+            geometries: BTreeMap::new(),
+        };
+        for (name, geometry) in &description.geometries {
+            let label = format!("geometry `{name}`");
+            if matches!(geometry, ObjectDescription::Instance { .. }) {
+                return Err(builder.invalid(&label, "geometry can't be an instance"));
+            }
+            let built: Arc<dyn Hittable + Sync + Send> =
+                builder.build(&label, geometry.clone())?.into();
+            builder.geometries.insert(name.clone(), built);
+        }
+
+        let mut objects: Vec<Box<dyn Hittable + Sync + Send>> = Vec::new();
+        let mut lights = List::default();
+        for (index, object) in description.objects.into_iter().enumerate() {
+            let label = format!("object {index}");
+            let is_light = object
+                .material(&description.geometries)
+                .is_some_and(|material| emissive.contains(material));
+            let can_be_sampled = object.can_be_sampled(&description.geometries);
+            let built: Arc<dyn Hittable + Sync + Send> = builder.build(&label, object)?.into();
+            if is_light {
+                if !can_be_sampled {
+                    return Err(builder.invalid(
+                        &label,
+                        "only spheres, triangles, quads, disks, boxes and instances of them can \
+                         emit light",
+                    ));
+                }
+                lights.add(Box::new(built.clone()));
+            }
+            objects.push(Box::new(built));
+        }
+
+        Ok(Self {
+            camera,
+            world: BvhNode::new(objects),
+            lights,
+        })
+    }
+}
+
+type Materials = BTreeMap<String, Arc<dyn Material + Sync + Send>>;
+
+/// Builds objects, resolving the names of materials and geometries they refer to
+struct ObjectBuilder<'a> {
+    scene_path: &'a Path,
+    materials: &'a Materials,
+    geometries: BTreeMap<String, Arc<dyn Hittable + Sync + Send>>,
+}
+
+impl ObjectBuilder<'_> {
+    fn invalid(&self, label: &str, message: impl Display) -> Error {
+        Error::Invalid {
+            path: self.scene_path.to_owned(),
+            message: format!("{label}: {message}"),
+        }
+    }
+
+    fn material(&self, label: &str, name: &str) -> Result<Arc<dyn Material + Sync + Send>, Error> {
+        self.materials
+            .get(name)
+            .cloned()
+            .ok_or_else(|| self.invalid(label, format_args!("unknown material `{name}`")))
+    }
+
+    fn build(
+        &self,
+        label: &str,
+        object: ObjectDescription,
+    ) -> Result<Box<dyn Hittable + Sync + Send>, Error> {
+        let invalid = |message: String| self.invalid(label, message);
+        Ok(match object {
+            ObjectDescription::Sphere {
+                center,
+                radius,
+                material,
+            } => Box::new(Sphere::new(
+                Point3::new(center),
+                require_positive("sphere radius", radius).map_err(invalid)?,
+                self.material(label, &material)?,
+            )),
+            ObjectDescription::MovingSphere {
+                keyframes,
+                radius,
+                material,
+            } => {
+                let radius = require_positive("sphere radius", radius).map_err(invalid)?;
+                if keyframes.is_empty() {
+                    return Err(invalid(
+                        "moving sphere needs at least one keyframe".to_owned(),
+                    ));
+                }
+                if let Some(keyframe) = keyframes.iter().find(|keyframe| !keyframe.time.is_finite())
+                {
+                    return Err(invalid(format!(
+                        "keyframe time must be finite, found {}",
+                        keyframe.time
+                    )));
+                }
+                Box::new(MovingSphere::with_keyframes(
+                    keyframes
+                        .into_iter()
+                        .map(|keyframe| (keyframe.time, Point3::new(keyframe.center)))
+                        .collect(),
+                    radius,
+                    self.material(label, &material)?,
+                ))
+            }
+            ObjectDescription::Triangle { vertices, material } => {
+                let [a, b, c] = vertices.map(Point3::new);
+                if (b - a).cross(&(c - a)).near_zero() {
+                    return Err(invalid(
+                        "triangle vertices must not be collinear".to_owned(),
+                    ));
+                }
+                Box::new(Triangle::new([a, b, c], self.material(label, &material)?))
+            }
+            ObjectDescription::Quad {
+                corner,
+                u,
+                v,
+                material,
+            } => {
+                let (u, v) = (Vec3::new(u), Vec3::new(v));
+                if u.cross(&v).near_zero() {
+                    return Err(invalid("quad edges must not be parallel".to_owned()));
+                }
+                Box::new(Quad::new(
+                    Point3::new(corner),
+                    u,
+                    v,
+                    self.material(label, &material)?,
+                ))
+            }
+            ObjectDescription::Plane {
+                point,
+                normal,
+                material,
+            } => Box::new(Plane::new(
+                Point3::new(point),
+                require_direction(normal).map_err(invalid)?,
+                self.material(label, &material)?,
+            )),
+            ObjectDescription::Disk {
+                center,
+                normal,
+                radius,
+                material,
+            } => Box::new(Disk::new(
+                Point3::new(center),
+                require_direction(normal).map_err(invalid)?,
+                require_positive("disk radius", radius).map_err(invalid)?,
+                self.material(label, &material)?,
+            )),
+            ObjectDescription::Box { a, b, material } => {
+                require_extent("box", a, b).map_err(invalid)?;
+                Box::new(Cuboid::new(
+                    Point3::new(a),
+                    Point3::new(b),
+                    self.material(label, &material)?,
+                ))
+            }
+            ObjectDescription::Mesh { path } => {
+                let directory = self.scene_path.parent().unwrap_or(Path::new(""));
+                Box::new(BvhNode::from(obj::load(directory.join(path))?))
+            }
+            ObjectDescription::ConstantMedium {
+                boundary,
+                density,
+                material,
+            } => Box::new(ConstantMedium::new(
+                self.build(label, *boundary)?,
+                require_positive("density", density).map_err(invalid)?,
+                self.material(label, &material)?,
+            )),
+            ObjectDescription::Volume {
+                path,
+                format,
+                a,
+                b,
+                density,
+                material,
+            } => {
+                require_extent("volume", a, b).map_err(invalid)?;
+                let directory = self.scene_path.parent().unwrap_or(Path::new(""));
+                let path = directory.join(path);
+                let grid = match format {
+                    GridFormat::Raw => VoxelGrid::load_raw(path)?,
+                    GridFormat::Sparse => VoxelGrid::load_sparse(path)?,
+                };
+                Box::new(HeterogeneousMedium::new(
+                    Arc::new(grid),
+                    Point3::new(a),
+                    Point3::new(b),
+                    require_positive("density", density).map_err(invalid)?,
+                    self.material(label, &material)?,
+                ))
+            }
+            ObjectDescription::Instance {
+                geometry,
+                material,
+                translate,
+                rotate,
+                scale,
+            } => {
+                let shared = self
+                    .geometries
+                    .get(&geometry)
+                    .cloned()
+                    .ok_or_else(|| invalid(format!("unknown geometry `{geometry}`")))?;
+                let scale = match scale {
+                    ScaleDescription::Uniform(factor) => [factor; 3],
+                    ScaleDescription::PerAxis(factors) => factors,
+                };
+                let matrix = Matrix4::translation(Vec3::new(translate))
+                    * Matrix4::rotation(Vec3::new([0.0, 0.0, 1.0]), rotate[2])
+                    * Matrix4::rotation(Vec3::new([0.0, 1.0, 0.0]), rotate[1])
+                    * Matrix4::rotation(Vec3::new([1.0, 0.0, 0.0]), rotate[0])
+                    * Matrix4::scaling(Vec3::new(scale));
+                let instance = Instance::new(shared, matrix)
+                    .ok_or_else(|| invalid("instance transform can't be inverted".to_owned()))?;
+                match material {
+                    Some(material) => {
+                        Box::new(instance.with_material(self.material(label, &material)?))
+                    }
+                    None => Box::new(instance),
+                }
+            }
+        })
+    }
+}
+
+impl CameraDescription {
+    fn build(self) -> Result<Camera, String> {
+        require_positive("camera aspect ratio", self.aspect_ratio)?;
+        if self.image_width == 0 {
+            return Err("camera image width must be positive".to_owned());
+        }
+        if self.samples_per_pixel == 0 {
+            return Err("camera samples per pixel must be positive".to_owned());
+        }
+        if !(0.0 < self.vfov && self.vfov < 180.0) {
+            return Err(format!(
+                "camera vertical field of view must be between 0 and 180 degrees, found {}",
+                self.vfov
+            ));
+        }
+        if self.look_from == self.look_at {
+            return Err("camera look_from and look_at must differ".to_owned());
+        }
+        let view = Point3::new(self.look_at) - Point3::new(self.look_from);
+        if Vec3::new(self.relative_up).cross(&view).near_zero() {
+            return Err(format!(
+                "camera relative_up must not be zero or parallel to the view direction, found {:?}",
+                self.relative_up
+            ));
+        }
+        require_positive("camera focus distance", self.focus_distance)?;
+        let [open, close] = self.shutter;
+        if !(open.is_finite() && close.is_finite() && open <= close) {
+            return Err(format!(
+                "camera shutter must open before it closes, found {:?}",
+                self.shutter
+            ));
+        }
+        Ok(Camera::new(
+            self.aspect_ratio,
+            self.image_width,
+            self.samples_per_pixel,
+            self.max_depth,
+            self.vfov,
+            Point3::new(self.look_from),
+            Point3::new(self.look_at),
+            Vec3::new(self.relative_up),
+            self.defocus_angle,
+            self.focus_distance,
+        )
+        .with_shutter(Interval::new(open, close))
+        .with_roulette_depth(self.roulette_depth)
+        .with_spectral(self.spectral))
+    }
+}
+
+impl BackgroundDescription {
+    /// Builds the background, resolving the image path relative to the scene file
+    fn build(self, scene_path: &Path) -> Result<Background, Error> {
+        Ok(match self {
+            Self::Solid { color } => Background::Solid(Color::new(color)),
+            Self::Gradient { bottom, top } => Background::Gradient {
+                bottom: Color::new(bottom),
+                top: Color::new(top),
+            },
+            Self::Environment {
+                path,
+                rotation,
+                intensity,
+            } => {
+                if !(intensity.is_finite() && intensity >= 0.0) {
+                    return Err(Error::Invalid {
+                        path: scene_path.to_owned(),
+                        message: format!(
+                            "background intensity must be finite and non-negative, found \
+                             {intensity}"
+                        ),
+                    });
+                }
+                let path = scene_path.parent().unwrap_or(Path::new("")).join(path);
+                Background::Environment(
+                    EnvironmentMap::load(&path, rotation, intensity)
+                        .map_err(|source| Error::Image { path, source })?,
+                )
+            }
+        })
+    }
+}
+
+type Textures = BTreeMap<String, Arc<dyn Texture + Sync + Send>>;
+
+impl TextureDescription {
+    /// Builds the texture, resolving the image path relative to the scene file
+    fn build(self, name: &str, scene_path: &Path) -> Result<Arc<dyn Texture + Sync + Send>, Error> {
+        Ok(match self {
+            Self::Solid { color } => Arc::new(SolidColor::new(Color::new(color))),
+            Self::Checker { scale, even, odd } => {
+                let scale =
+                    require_positive("checker scale", scale).map_err(|message| Error::Invalid {
+                        path: scene_path.to_owned(),
+                        message: format!("texture `{name}`: {message}"),
+                    })?;
+                Arc::new(Checker::new(
+                    scale,
+                    Arc::new(SolidColor::new(Color::new(even))),
+                    Arc::new(SolidColor::new(Color::new(odd))),
+                ))
+            }
+            Self::Image { path } => {
+                let path = scene_path.parent().unwrap_or(Path::new("")).join(path);
+                Arc::new(ImageTexture::load(&path).map_err(|source| Error::Image { path, source })?)
+            }
+            Self::Noise {
+                pattern,
+                scale,
+                octaves,
+                seed,
+                color,
+            } => {
+                let scale =
+                    require_positive("noise scale", scale).map_err(|message| Error::Invalid {
+                        path: scene_path.to_owned(),
+                        message: format!("texture `{name}`: {message}"),
+                    })?;
+                let pattern = match pattern {
+                    PatternDescription::Smooth => Pattern::Smooth,
+                    PatternDescription::Turbulence => Pattern::Turbulence,
+                    PatternDescription::Marble => Pattern::Marble,
+                };
+                Arc::new(NoiseTexture::new(
+                    seed,
+                    pattern,
+                    scale,
+                    octaves,
+                    Color::new(color),
+                ))
+            }
+        })
+    }
+}
+
+impl AlbedoDescription {
+    fn build(self, textures: &Textures) -> Result<Arc<dyn Texture + Sync + Send>, String> {
+        match self {
+            Self::Color(color) => Ok(Arc::new(SolidColor::new(Color::new(color)))),
+            Self::Texture(name) => textures
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| format!("unknown texture `{name}`")),
+        }
+    }
+}
+
+impl DispersionDescription {
+    const fn build(self) -> Dispersion {
+        match self {
+            Self::Bk7 => Dispersion::BK7,
+            Self::Diamond => Dispersion::DIAMOND,
+            Self::Cauchy { a, b } => Dispersion::Cauchy { a, b },
+            Self::Sellmeier { b, c } => Dispersion::Sellmeier { b, c },
+        }
+    }
+}
+
+impl ParameterDescription {
+    /// Builds the parameter as texture, checking that a constant value is in the range
+    fn build(
+        self,
+        name: &str,
+        range: RangeInclusive<f64>,
+        textures: &Textures,
+    ) -> Result<Arc<dyn Texture + Sync + Send>, String> {
+        match self {
+            Self::Value(value) => {
+                if !range.contains(&value) {
+                    return Err(format!(
+                        "{name} must be between {} and {}, found {value}",
+                        range.start(),
+                        range.end()
+                    ));
+                }
+                Ok(Arc::new(SolidColor::new(Color::new([value; 3]))))
+            }
+            Self::Texture(name) => AlbedoDescription::Texture(name).build(textures),
+        }
+    }
+}
+
+impl MaterialDescription {
+    fn build(self, textures: &Textures) -> Result<Arc<dyn Material + Sync + Send>, String> {
+        Ok(match self {
+            Self::Lambertian { albedo } => {
+                Arc::new(Lambertian::from_texture(albedo.build(textures)?))
+            }
+            Self::Metal { albedo, fuzz } => {
+                if !(0.0..=1.0).contains(&fuzz) {
+                    return Err(format!("fuzz must be between 0 and 1, found {fuzz}"));
+                }
+                Arc::new(Metal::from_texture(albedo.build(textures)?, fuzz))
+            }
+            Self::Dielectric {
+                refraction_index,
+                dispersion,
+                roughness,
+                absorption,
+                color,
+                distance,
+            } => {
+                if !(0.0..=1.0).contains(&roughness) {
+                    return Err(format!(
+                        "roughness must be between 0 and 1, found {roughness}"
+                    ));
+                }
+                let dispersion = dispersion.map(DispersionDescription::build);
+                let refraction_index = match (refraction_index, dispersion) {
+                    (Some(refraction_index), None) => {
+                        require_positive("refraction index", refraction_index)?
+                    }
+                    (None, Some(_)) if roughness > 0.0 => {
+                        return Err("dispersion needs a smooth dielectric".to_owned());
+                    }
+                    (None, Some(dispersion)) => require_positive(
+                        "refraction index of the dispersion",
+                        dispersion.refraction_index(REFERENCE_WAVELENGTH),
+                    )?,
+                    _ => {
+                        return Err(
+                            "dielectric needs either a refraction index or a dispersion".to_owned()
+                        );
+                    }
+                };
+                let absorption = match (absorption, color) {
+                    (None, None) => Color::default(),
+                    (Some(absorption), None) => {
+                        if !absorption
+                            .iter()
+                            .all(|value| value.is_finite() && *value >= 0.0)
+                        {
+                            return Err(format!(
+                                "absorption must be finite and non-negative, found {absorption:?}"
+                            ));
+                        }
+                        Color::new(absorption)
+                    }
+                    (None, Some(color)) => {
+                        if !color.iter().all(|value| (0.0..=1.0).contains(value)) {
+                            return Err(format!("color must be between 0 and 1, found {color:?}"));
+                        }
+                        absorption_from_color(
+                            Color::new(color),
+                            require_positive("distance", distance)?,
+                        )
+                    }
+                    (Some(_), Some(_)) => {
+                        return Err("dielectric needs either absorption or color".to_owned());
+                    }
+                };
+                if roughness > 0.0 {
+                    Arc::new(
+                        RoughDielectric::new(refraction_index, roughness)
+                            .with_absorption(absorption),
+                    )
+                } else {
+                    let dielectric = Dielectric::new(refraction_index).with_absorption(absorption);
+                    Arc::new(match dispersion {
+                        Some(dispersion) => dielectric.with_dispersion(dispersion),
+                        None => dielectric,
+                    })
+                }
+            }
+            Self::Conductor {
+                metal,
+                eta,
+                k,
+                roughness,
+            } => {
+                let ior = match (metal, eta, k) {
+                    (Some(metal), None, None) => match metal {
+                        MetalPreset::Gold => ComplexIor::GOLD,
+                        MetalPreset::Copper => ComplexIor::COPPER,
+                        MetalPreset::Aluminium => ComplexIor::ALUMINIUM,
+                        MetalPreset::Silver => ComplexIor::SILVER,
+                    },
+                    (None, Some(eta), Some(k)) => {
+                        if !eta
+                            .iter()
+                            .chain(&k)
+                            .all(|value| value.is_finite() && *value >= 0.0)
+                        {
+                            return Err(format!(
+                                "eta and k must be finite and non-negative, found {eta:?} and {k:?}"
+                            ));
+                        }
+                        ComplexIor::new(eta, k)
+                    }
+                    _ => return Err("conductor needs either a metal or both eta and k".to_owned()),
+                };
+                if !(0.0..=1.0).contains(&roughness) {
+                    return Err(format!(
+                        "roughness must be between 0 and 1, found {roughness}"
+                    ));
+                }
+                Arc::new(Conductor::new(ior, roughness))
+            }
+            Self::Principled {
+                base_color,
+                metallic,
+                roughness,
+                specular,
+                specular_tint,
+                sheen,
+                clearcoat,
+                transmission,
+                refraction_index,
+            } => {
+                let fraction = |name, parameter: Option<ParameterDescription>| {
+                    parameter
+                        .map(|parameter| parameter.build(name, 0.0..=1.0, textures))
+                        .transpose()
+                };
+                let mut material = Principled::from_texture(base_color.build(textures)?);
+                if let Some(metallic) = fraction("metallic", metallic)? {
+                    material = material.with_metallic(metallic);
+                }
+                if let Some(roughness) = fraction("roughness", roughness)? {
+                    material = material.with_roughness(roughness);
+                }
+                if let Some(specular) = fraction("specular", specular)? {
+                    material = material.with_specular(specular);
+                }
+                if let Some(specular_tint) = fraction("specular tint", specular_tint)? {
+                    material = material.with_specular_tint(specular_tint);
+                }
+                if let Some(sheen) = fraction("sheen", sheen)? {
+                    material = material.with_sheen(sheen);
+                }
+                if let Some(clearcoat) = fraction("clearcoat", clearcoat)? {
+                    material = material.with_clearcoat(clearcoat);
+                }
+                if let Some(transmission) = fraction("transmission", transmission)? {
+                    material = material.with_transmission(transmission);
+                }
+                if let Some(refraction_index) = refraction_index {
+                    material = material.with_refraction_index(refraction_index.build(
+                        "refraction index",
+                        f64::MIN_POSITIVE..=f64::MAX,
+                        textures,
+                    )?);
+                }
+                Arc::new(material)
+            }
+            Self::Isotropic { albedo } => {
+                Arc::new(Isotropic::from_texture(albedo.build(textures)?))
+            }
+            Self::DiffuseLight { emit } => {
+                if !emit
+                    .iter()
+                    .all(|component| component.is_finite() && *component >= 0.0)
+                {
+                    return Err(format!(
+                        "emitted light must be finite and non-negative, found {emit:?}"
+                    ));
+                }
+                Arc::new(DiffuseLight::new(Color::new(emit)))
+            }
+        })
+    }
+}
+
+/// Returns the normal of an object as vector, if it has a direction
+fn require_direction(normal: [f64; 3]) -> Result<Vec3, String> {
+    let normal = Vec3::new(normal);
+    if normal.near_zero() || !normal.length().is_finite() {
+        Err(format!("normal must have a direction, found {normal:?}"))
+    } else {
+        Ok(normal)
+    }
+}
+
+/// Checks that the opposite corners `a` and `b` of an axis-aligned box span a volume
+fn require_extent(name: &str, a: [f64; 3], b: [f64; 3]) -> Result<(), String> {
+    if (0..3).all(|axis| a[axis] != b[axis]) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{name} corners must differ on every axis, found {a:?} and {b:?}"
+        ))
+    }
+}
+
+/// Returns the value if it is strictly positive, which also rejects NaN
+fn require_positive(name: &str, value: f64) -> Result<f64, String> {
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("{name} must be positive, found {value}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the message of the validation error of the scene
+    fn invalid(text: &str) -> String {
+        match Scene::parse(Path::new("test.toml"), text) {
+            Err(Error::Invalid { message, .. }) => message,
+            Err(error) => panic!("expected a validation error, found {error}"),
+            Ok(_) => panic!("expected a validation error"),
+        }
+    }
+
+    /// Returns the message of the validation error of a material with the given fields
+    fn invalid_material(fields: &str) -> String {
+        let message = invalid(&format!("[materials.test]\n{fields}"));
+        message
+            .strip_prefix("material `test`: ")
+            .unwrap_or_else(|| panic!("unexpected message {message}"))
+            .to_owned()
+    }
+
+    #[test]
+    fn valid_scene_collects_its_lights() {
+        let text = r#"
+            [materials.white]
+            type = "lambertian"
+            albedo = [0.7, 0.7, 0.7]
+
+            [materials.light]
+            type = "diffuse_light"
+            emit = [4, 4, 4]
+
+            [[objects]]
+            type = "sphere"
+            center = [0, 0, -1]
+            radius = 0.5
+            material = "white"
+
+            [[objects]]
+            type = "quad"
+            corner = [-1, 2, -2]
+            u = [2, 0, 0]
+            v = [0, 0, 2]
+            material = "light"
+        "#;
+        let scene = Scene::parse(Path::new("test.toml"), text).unwrap();
+        assert_eq!(scene.lights.len(), 1);
+    }
+
+    #[test]
+    fn emissive_instances_are_lights() {
+        let text = r#"
+            [materials.light]
+            type = "diffuse_light"
+            emit = [4, 4, 4]
+
+            [materials.white]
+            type = "lambertian"
+            albedo = [0.7, 0.7, 0.7]
+
+            [geometries.panel]
+            type = "quad"
+            corner = [0, 0, 0]
+            u = [1, 0, 0]
+            v = [0, 0, 1]
+            material = "white"
+
+            [[objects]]
+            type = "instance"
+            geometry = "panel"
+            material = "light"
+            translate = [0, 2, 0]
+
+            [[objects]]
+            type = "instance"
+            geometry = "panel"
+            scale = [2, 1, 1]
+        "#;
+        let scene = Scene::parse(Path::new("test.toml"), text).unwrap();
+        assert_eq!(scene.lights.len(), 1);
+    }
+
+    #[test]
+    fn emissive_objects_must_be_sampled() {
+        let message = invalid(
+            r#"
+            [materials.light]
+            type = "diffuse_light"
+            emit = [4, 4, 4]
+
+            [[objects]]
+            type = "moving_sphere"
+            keyframes = [{ time = 0, center = [0, 0, 0] }, { time = 1, center = [1, 0, 0] }]
+            radius = 1
+            material = "light"
+            "#,
+        );
+        assert!(message.starts_with("object 0: only spheres"), "{message}");
+    }
+
+    #[test]
+    fn objects_are_validated() {
+        let message = invalid(
+            r#"
+            [[objects]]
+            type = "sphere"
+            center = [0, 0, 0]
+            radius = 1
+            material = "missing"
+            "#,
+        );
+        assert_eq!(message, "object 0: unknown material `missing`");
+
+        let message = invalid(
+            r#"
+            [materials.white]
+            type = "lambertian"
+            albedo = [0.5, 0.5, 0.5]
+
+            [[objects]]
+            type = "sphere"
+            center = [0, 0, 0]
+            radius = -1
+            material = "white"
+            "#,
+        );
+        assert_eq!(
+            message,
+            "object 0: sphere radius must be positive, found -1"
+        );
+    }
+
+    #[test]
+    fn flat_volumes_are_rejected() {
+        let message = invalid(
+            r#"
+            [materials.smoke]
+            type = "isotropic"
+            albedo = [1, 1, 1]
+
+            [[objects]]
+            type = "volume"
+            path = "missing.raw"
+            a = [0, 0, 0]
+            b = [1, 0, 1]
+            material = "smoke"
+            "#,
+        );
+        assert!(
+            message.starts_with("object 0: volume corners must differ on every axis"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn degenerate_triangles_are_rejected() {
+        let message = invalid(
+            r#"
+            [materials.white]
+            type = "lambertian"
+            albedo = [0.5, 0.5, 0.5]
+
+            [[objects]]
+            type = "triangle"
+            vertices = [[0, 0, 0], [1, 1, 1], [2, 2, 2]]
+            material = "white"
+            "#,
+        );
+        assert_eq!(message, "object 0: triangle vertices must not be collinear");
+    }
+
+    #[test]
+    fn camera_needs_an_up_direction() {
+        let message = invalid("[camera]\nlook_at = [0, 0, -1]\nrelative_up = [0, 0, 2]");
+        assert!(
+            message.starts_with("camera relative_up must not be zero or parallel"),
+            "{message}"
+        );
+        let message = invalid("[camera]\nrelative_up = [0, 0, 0]");
+        assert!(
+            message.starts_with("camera relative_up must not be zero or parallel"),
+            "{message}"
+        );
+        let message = invalid("[camera]\nlook_at = [0, 0, 0]");
+        assert_eq!(message, "camera look_from and look_at must differ");
+    }
+
+    #[test]
+    fn metal_fuzz_is_a_fraction() {
+        let message = invalid_material("type = \"metal\"\nalbedo = [1, 1, 1]\nfuzz = 1.5");
+        assert_eq!(message, "fuzz must be between 0 and 1, found 1.5");
+    }
+
+    #[test]
+    fn conductors_need_a_valid_index() {
+        let message = invalid_material("type = \"conductor\"\neta = [1, 1, 1]");
+        assert_eq!(message, "conductor needs either a metal or both eta and k");
+        let message = invalid_material("type = \"conductor\"\neta = [1, -1, 1]\nk = [1, 1, 1]");
+        assert!(message.starts_with("eta and k must be finite"), "{message}");
+        let message = invalid_material("type = \"conductor\"\nmetal = \"gold\"\nroughness = 2");
+        assert_eq!(message, "roughness must be between 0 and 1, found 2");
+    }
+
+    #[test]
+    fn dielectric_roughness_is_a_fraction() {
+        let message =
+            invalid_material("type = \"dielectric\"\nrefraction_index = 1.5\nroughness = -0.1");
+        assert_eq!(message, "roughness must be between 0 and 1, found -0.1");
+    }
+
+    #[test]
+    fn dielectric_absorption_is_validated() {
+        let glass = "type = \"dielectric\"\nrefraction_index = 1.5";
+        let message = invalid_material(&format!("{glass}\nabsorption = [0, -1, 0]"));
+        assert!(
+            message.starts_with("absorption must be finite and non-negative"),
+            "{message}"
+        );
+        let message = invalid_material(&format!("{glass}\ncolor = [0.5, 1.5, 0.5]"));
+        assert!(
+            message.starts_with("color must be between 0 and 1"),
+            "{message}"
+        );
+        let message = invalid_material(&format!("{glass}\ncolor = [0.5, 0.5, 0.5]\ndistance = 0"));
+        assert_eq!(message, "distance must be positive, found 0");
+        let message = invalid_material(&format!(
+            "{glass}\nabsorption = [1, 1, 1]\ncolor = [0.5, 0.5, 0.5]"
+        ));
+        assert_eq!(message, "dielectric needs either absorption or color");
+    }
+
+    #[test]
+    fn dielectric_dispersion_is_validated() {
+        let message = invalid_material("type = \"dielectric\"");
+        assert_eq!(
+            message,
+            "dielectric needs either a refraction index or a dispersion"
+        );
+        let message = invalid_material(
+            "type = \"dielectric\"\nrefraction_index = 1.5\ndispersion = { type = \"bk7\" }",
+        );
+        assert_eq!(
+            message,
+            "dielectric needs either a refraction index or a dispersion"
+        );
+        let message = invalid_material(
+            "type = \"dielectric\"\nroughness = 0.2\ndispersion = { type = \"diamond\" }",
+        );
+        assert_eq!(message, "dispersion needs a smooth dielectric");
+        let message = invalid_material(
+            "type = \"dielectric\"\ndispersion = { type = \"cauchy\", a = -1, b = 0 }",
+        );
+        assert!(
+            message.starts_with("refraction index of the dispersion must be positive"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn principled_parameters_are_in_range() {
+        let base = "type = \"principled\"\nbase_color = [0.5, 0.5, 0.5]";
+        let message = invalid_material(&format!("{base}\nmetallic = 1.5"));
+        assert_eq!(message, "metallic must be between 0 and 1, found 1.5");
+        let message = invalid_material(&format!("{base}\nclearcoat = -0.5"));
+        assert_eq!(message, "clearcoat must be between 0 and 1, found -0.5");
+        let message = invalid_material(&format!("{base}\nrefraction_index = 0"));
+        assert!(
+            message.starts_with("refraction index must be between"),
+            "{message}"
+        );
+        let message = invalid_material(&format!("{base}\nroughness = \"missing\""));
+        assert_eq!(message, "unknown texture `missing`");
+    }
+}
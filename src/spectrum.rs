@@ -0,0 +1,255 @@
+//! Spectral rendering with hero wavelength sampling (Wilkie et al. 2014). Every camera ray
+//! carries three wavelengths spread evenly over the visible range, and in spectral mode the
+//! three channels of colors along its path hold values at these wavelengths instead of red,
+//! green and blue. RGB colors of the scene are upsampled to smooth spectra, and the values
+//! found at the wavelengths are accumulated to CIE XYZ and converted back to linear sRGB.
+
+use std::{array, sync::LazyLock};
+
+use crate::{color::Color, matrix::Matrix4, rng::random, vec3::Vec3};
+
+/// Shortest wavelength sampled, in nanometres
+pub const MIN_WAVELENGTH: f64 = 360.0;
+
+/// Longest wavelength sampled, in nanometres
+pub const MAX_WAVELENGTH: f64 = 830.0;
+
+const WAVELENGTH_RANGE: f64 = MAX_WAVELENGTH - MIN_WAVELENGTH;
+
+/// Number of wavelengths carried by a ray, one per color channel
+const COUNT: usize = 3;
+
+/// Number of steps integrating over the sampled range, to build the conversions
+const INTEGRATION_STEPS: usize = 940;
+
+/// Matrix converting CIE XYZ to linear sRGB
+const XYZ_TO_SRGB: [[f64; 3]; 3] = [
+    [3.2406, -1.5372, -0.4986],
+    [-0.9689, 1.8758, 0.0415],
+    [0.0557, -0.2040, 1.0570],
+];
+
+/// Wavelengths where the upsampled spectra cross from blue to green and from green to red
+const BASIS_EDGES: [f64; 2] = [490.0, 590.0];
+
+/// Width of the transitions between the upsampled spectra
+const BASIS_SMOOTHNESS: f64 = 12.0;
+
+/// Returns the CIE 1931 color matching functions at the wavelength, by the multi-lobe fit of
+/// Wyman, Sloan and Shirley 2013
+pub fn color_matching(wavelength: f64) -> Vec3 {
+    // Gaussian with a different width on either side of its mean
+    let lobe = |mean: f64, below: f64, above: f64| {
+        let width = if wavelength < mean { below } else { above };
+        (-0.5 * ((wavelength - mean) / width).powi(2)).exp()
+    };
+    Vec3::new([
+        1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+            - 0.065 * lobe(501.1, 20.4, 26.2),
+        0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+        1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+    ])
+}
+
+/// Returns the smooth red, green and blue spectra that RGB colors are upsampled with, which
+/// sum to one at every wavelength
+fn basis(wavelength: f64) -> [f64; 3] {
+    let step = |edge: f64| 1.0 / (1.0 + (-(wavelength - edge) / BASIS_SMOOTHNESS).exp());
+    let red = step(BASIS_EDGES[1]);
+    let blue = 1.0 - step(BASIS_EDGES[0]);
+    [red, 1.0 - red - blue, blue]
+}
+
+/// Converts CIE XYZ to linear sRGB
+fn xyz_to_srgb(xyz: Vec3) -> Color {
+    Color::new(XYZ_TO_SRGB.map(|row| Vec3::new(row).dot(&xyz)))
+}
+
+/// Divides the color by the color of white, channel by channel
+fn balance(color: Color, white: Color) -> Color {
+    Color::new(array::from_fn(|channel| color[channel] / white[channel]))
+}
+
+/// Linear conversions between spectra and sRGB, integrated once
+struct Conversion {
+    /// sRGB of the flat spectrum of value one, divided out so it converts to white
+    white: Color,
+
+    /// Weights of the basis spectra upsampling an RGB color, so that converting the spectrum
+    /// back gives the same color
+    rgb_to_basis: Matrix4,
+}
+
+static CONVERSION: LazyLock<Conversion> = LazyLock::new(|| {
+    let step = WAVELENGTH_RANGE / INTEGRATION_STEPS as f64;
+    let wavelengths = (0..INTEGRATION_STEPS).map(|i| MIN_WAVELENGTH + (i as f64 + 0.5) * step);
+
+    let mut white = Vec3::default();
+    let mut basis_xyz = [Vec3::default(); 3];
+    for wavelength in wavelengths {
+        let color_matching = color_matching(wavelength) * step;
+        white += color_matching;
+        for (xyz, weight) in basis_xyz.iter_mut().zip(basis(wavelength)) {
+            *xyz += weight * color_matching;
+        }
+    }
+    let white = xyz_to_srgb(white);
+
+    // Colors of the basis spectra as columns, whose inverse finds the weights of a color
+    let basis_rgb = basis_xyz.map(|xyz| balance(xyz_to_srgb(xyz), white));
+    let basis_to_rgb = Matrix4::new([
+        [basis_rgb[0].x(), basis_rgb[1].x(), basis_rgb[2].x(), 0.0],
+        [basis_rgb[0].y(), basis_rgb[1].y(), basis_rgb[2].y(), 0.0],
+        [basis_rgb[0].z(), basis_rgb[1].z(), basis_rgb[2].z(), 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+    Conversion {
+        white,
+        rgb_to_basis: basis_to_rgb
+            .inverse()
+            .expect("basis spectra have independent colors"),
+    }
+});
+
+/// Wavelengths in nanometres carried by a ray, the first being the hero wavelength that
+/// decides the path where scattering depends on the wavelength
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wavelengths {
+    values: [f64; COUNT],
+
+    /// Whether only the hero wavelength is still carried, after the path scattered
+    /// differently for each wavelength
+    hero_only: bool,
+}
+
+impl Wavelengths {
+    /// Samples a uniformly distributed hero wavelength, with the others at equal distances
+    /// from it, wrapping around the visible range
+    pub fn sample() -> Self {
+        let hero_offset = random::<f64>() * WAVELENGTH_RANGE;
+        Self {
+            values: array::from_fn(|i| {
+                let offset = hero_offset + i as f64 * WAVELENGTH_RANGE / COUNT as f64;
+                MIN_WAVELENGTH + offset % WAVELENGTH_RANGE
+            }),
+            hero_only: false,
+        }
+    }
+
+    pub const fn hero(&self) -> f64 {
+        self.values[0]
+    }
+
+    /// Returns the wavelengths without the secondary ones, for rays scattered into a
+    /// direction that depends on the wavelength, which only the hero wavelength takes
+    pub const fn into_hero_only(self) -> Self {
+        Self {
+            hero_only: true,
+            ..self
+        }
+    }
+
+    /// Returns the factor for the values along a path scattering from these wavelengths to
+    /// the `scattered` wavelengths. When the secondary wavelengths are dropped, the hero
+    /// wavelength accounts for all of them to keep the estimate unbiased.
+    pub const fn termination_weight(&self, scattered: &Self) -> Color {
+        if scattered.hero_only && !self.hero_only {
+            Color::new([COUNT as f64, 0.0, 0.0])
+        } else {
+            Color::new([1.0; 3])
+        }
+    }
+
+    /// Returns the values of the upsampled spectrum of the linear sRGB color at the
+    /// wavelengths. Colors too saturated for the smooth spectra are clipped to non-negative
+    /// values.
+    pub fn upsample(&self, color: Color) -> Color {
+        let weights = CONVERSION.rgb_to_basis.transform_vector(&color);
+        Color::new(self.values.map(|wavelength| {
+            let basis = basis(wavelength);
+            (0..3).map(|i| basis[i] * weights[i]).sum::<f64>().max(0.0)
+        }))
+    }
+
+    /// Converts values of a spectrum at the wavelengths to an estimate of its linear sRGB
+    /// color, through its CIE XYZ color
+    pub fn to_rgb(&self, values: Color) -> Color {
+        // Each wavelength is sampled uniformly over the range
+        let xyz = (0..COUNT)
+            .map(|i| values[i] * color_matching(self.values[i]))
+            .sum::<Vec3>()
+            * WAVELENGTH_RANGE
+            / COUNT as f64;
+        balance(xyz_to_srgb(xyz), CONVERSION.white)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::reseed;
+
+    const SAMPLES: usize = 50_000;
+
+    /// Estimates the color of upsampling the color and converting it back
+    fn round_trip(color: Color) -> Color {
+        (0..SAMPLES)
+            .map(|_| {
+                let wavelengths = Wavelengths::sample();
+                wavelengths.to_rgb(wavelengths.upsample(color))
+            })
+            .sum::<Color>()
+            / SAMPLES as f64
+    }
+
+    #[test]
+    fn white_is_a_flat_spectrum() {
+        reseed(21);
+        let wavelengths = Wavelengths::sample();
+        let values = wavelengths.upsample(Color::new([1.0; 3]));
+        assert!((values - Color::new([1.0; 3])).length() < 1e-9);
+        let white = round_trip(Color::new([1.0; 3]));
+        assert!((white - Color::new([1.0; 3])).length() < 0.02, "{white:?}");
+    }
+
+    #[test]
+    fn colors_survive_the_round_trip() {
+        reseed(22);
+        for color in [[0.8, 0.3, 0.1], [0.1, 0.5, 0.2], [0.2, 0.3, 0.7]] {
+            let color = Color::new(color);
+            let estimate = round_trip(color);
+            assert!((estimate - color).length() < 0.02, "{color:?} {estimate:?}");
+        }
+    }
+
+    #[test]
+    fn wavelengths_are_spread_over_the_range() {
+        reseed(23);
+        for _ in 0..100 {
+            let mut values = Wavelengths::sample().values;
+            assert!(
+                values
+                    .iter()
+                    .all(|value| (MIN_WAVELENGTH..MAX_WAVELENGTH).contains(value))
+            );
+            values.sort_by(f64::total_cmp);
+            for pair in values.windows(2) {
+                assert!((pair[1] - pair[0] - WAVELENGTH_RANGE / 3.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn dropping_secondary_wavelengths_keeps_the_estimate() {
+        let wavelengths = Wavelengths::sample();
+        let hero_only = wavelengths.into_hero_only();
+        assert_eq!(
+            wavelengths.termination_weight(&hero_only),
+            Color::new([3.0, 0.0, 0.0])
+        );
+        assert_eq!(
+            hero_only.termination_weight(&hero_only),
+            Color::new([1.0; 3])
+        );
+    }
+}
@@ -0,0 +1,12 @@
+use crate::{color::Color, vec3::Point3};
+
+pub mod checker;
+pub mod image_texture;
+pub mod noise;
+pub mod perlin;
+pub mod solid_color;
+
+/// Color varying across a surface, looked up by texture coordinates or position
+pub trait Texture {
+    fn value(&self, uv: [f64; 2], point: &Point3) -> Color;
+}
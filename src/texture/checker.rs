@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use crate::{color::Color, texture::Texture, vec3::Point3};
+
+/// Three-dimensional checker pattern alternating between two textures
+pub struct Checker {
+    scale_inverse: f64,
+    even: Arc<dyn Texture + Sync + Send>,
+    odd: Arc<dyn Texture + Sync + Send>,
+}
+
+impl Checker {
+    /// Creates a checker pattern with cubes of size `scale`
+    pub fn new(
+        scale: f64,
+        even: Arc<dyn Texture + Sync + Send>,
+        odd: Arc<dyn Texture + Sync + Send>,
+    ) -> Self {
+        Self {
+            scale_inverse: 1.0 / scale,
+            even,
+            odd,
+        }
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, uv: [f64; 2], point: &Point3) -> Color {
+        let cell_sum = (0..3)
+            .map(|axis| (self.scale_inverse * point[axis]).floor() as i64)
+            .sum::<i64>();
+        if cell_sum % 2 == 0 {
+            self.even.value(uv, point)
+        } else {
+            self.odd.value(uv, point)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::solid_color::SolidColor;
+
+    const EVEN: Color = Color::new([1.0, 1.0, 1.0]);
+    const ODD: Color = Color::new([0.0, 0.0, 0.0]);
+
+    fn checker(scale: f64) -> Checker {
+        Checker::new(
+            scale,
+            Arc::new(SolidColor::new(EVEN)),
+            Arc::new(SolidColor::new(ODD)),
+        )
+    }
+
+    fn value(checker: &Checker, point: [f64; 3]) -> Color {
+        checker.value([0.0, 0.0], &Point3::new(point))
+    }
+
+    #[test]
+    fn neighboring_cells_alternate() {
+        let checker = checker(1.0);
+        assert_eq!(value(&checker, [0.5, 0.5, 0.5]), EVEN);
+        assert_eq!(value(&checker, [1.5, 0.5, 0.5]), ODD);
+        assert_eq!(value(&checker, [0.5, 1.5, 0.5]), ODD);
+        assert_eq!(value(&checker, [0.5, 0.5, 1.5]), ODD);
+        assert_eq!(value(&checker, [1.5, 1.5, 0.5]), EVEN);
+        assert_eq!(value(&checker, [1.5, 1.5, 1.5]), ODD);
+        // Cells below zero continue the pattern
+        assert_eq!(value(&checker, [-0.5, 0.5, 0.5]), ODD);
+        assert_eq!(value(&checker, [-0.5, -0.5, 0.5]), EVEN);
+        assert_eq!(value(&checker, [-1.5, 0.5, 0.5]), EVEN);
+    }
+
+    #[test]
+    fn scale_sets_the_cell_size() {
+        let checker = checker(2.5);
+        assert_eq!(value(&checker, [0.1, 0.1, 0.1]), EVEN);
+        assert_eq!(value(&checker, [2.4, 2.4, 2.4]), EVEN);
+        assert_eq!(value(&checker, [2.6, 2.4, 2.4]), ODD);
+        assert_eq!(value(&checker, [-2.4, 0.1, 0.1]), ODD);
+        assert_eq!(value(&checker, [-2.6, 0.1, 0.1]), EVEN);
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use image::{DynamicImage, ImageResult, Rgb32FImage};
+
+use crate::{
+    color::{Color, srgb_to_linear},
+    texture::Texture,
+    vec3::Point3,
+};
+
+/// Texture mapping an image over the `[0, 1]` texture coordinate square
+pub struct ImageTexture {
+    /// Linear colors, with the first row at the top of the image
+    image: Rgb32FImage,
+}
+
+impl ImageTexture {
+    pub const fn new(image: Rgb32FImage) -> Self {
+        Self { image }
+    }
+
+    /// Loads an image file. Integer formats are assumed to be sRGB encoded and are converted
+    /// to linear colors, floating point formats are assumed to be linear already.
+    pub fn load(path: impl AsRef<Path>) -> ImageResult<Self> {
+        let image = image::open(path)?;
+        let is_linear = matches!(
+            image,
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+        );
+        let mut image = image.into_rgb32f();
+        if !is_linear {
+            for component in image.iter_mut() {
+                *component = srgb_to_linear(f64::from(*component)) as f32;
+            }
+        }
+        Ok(Self::new(image))
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, uv: [f64; 2], _point: &Point3) -> Color {
+        let (width, height) = self.image.dimensions();
+        if width == 0 || height == 0 {
+            // Debugging aid for missing texture data
+            return Color::new([0.0, 1.0, 1.0]);
+        }
+
+        // Clamp to the image, flipping v to image coordinates
+        let u = uv[0].clamp(0.0, 1.0);
+        let v = 1.0 - uv[1].clamp(0.0, 1.0);
+        let x = ((u * f64::from(width)) as u32).min(width - 1);
+        let y = ((v * f64::from(height)) as u32).min(height - 1);
+        let [r, g, b] = self.image.get_pixel(x, y).0;
+        Color::new([r.into(), g.into(), b.into()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use image::{Rgb, RgbImage};
+
+    use super::*;
+
+    /// 2×2 texture whose texel at column x and row y has the color (x, y, 0.5)
+    fn texture() -> ImageTexture {
+        ImageTexture::new(Rgb32FImage::from_fn(2, 2, |x, y| {
+            Rgb([x as f32, y as f32, 0.5])
+        }))
+    }
+
+    fn value(texture: &ImageTexture, uv: [f64; 2]) -> Color {
+        texture.value(uv, &Point3::default())
+    }
+
+    #[test]
+    fn v_points_up_the_image() {
+        let texture = texture();
+        assert_eq!(value(&texture, [0.25, 0.75]), Color::new([0.0, 0.0, 0.5]));
+        assert_eq!(value(&texture, [0.75, 0.75]), Color::new([1.0, 0.0, 0.5]));
+        assert_eq!(value(&texture, [0.25, 0.25]), Color::new([0.0, 1.0, 0.5]));
+        assert_eq!(value(&texture, [0.75, 0.25]), Color::new([1.0, 1.0, 0.5]));
+    }
+
+    #[test]
+    fn coordinates_are_clamped_to_the_image() {
+        let texture = texture();
+        assert_eq!(value(&texture, [0.0, 1.0]), Color::new([0.0, 0.0, 0.5]));
+        assert_eq!(value(&texture, [1.0, 0.0]), Color::new([1.0, 1.0, 0.5]));
+        assert_eq!(value(&texture, [-3.0, 7.0]), Color::new([0.0, 0.0, 0.5]));
+        assert_eq!(value(&texture, [7.0, -3.0]), Color::new([1.0, 1.0, 0.5]));
+    }
+
+    #[test]
+    fn empty_images_are_cyan() {
+        let texture = ImageTexture::new(Rgb32FImage::new(0, 0));
+        assert_eq!(value(&texture, [0.5, 0.5]), Color::new([0.0, 1.0, 1.0]));
+    }
+
+    /// Writes the image to a temporary file and loads it as a texture
+    fn load(extension: &str, save: impl FnOnce(&PathBuf) -> ImageResult<()>) -> ImageTexture {
+        let path = std::env::temp_dir().join(format!(
+            "image_texture_test_{}.{extension}",
+            std::process::id()
+        ));
+        save(&path).unwrap();
+        let texture = ImageTexture::load(&path);
+        fs::remove_file(&path).unwrap();
+        texture.unwrap()
+    }
+
+    #[test]
+    fn integer_images_are_decoded_from_srgb() {
+        let texture = load("png", |path| {
+            RgbImage::from_pixel(1, 1, Rgb([0, 128, 255])).save(path)
+        });
+        let color = value(&texture, [0.5, 0.5]);
+        let expected = Color::new([0.0, srgb_to_linear(128.0 / 255.0), 1.0]);
+        assert!((color - expected).length() < 1e-6, "{color:?}");
+        assert!((color.y() - 0.2158).abs() < 1e-4, "{color:?}");
+    }
+
+    #[test]
+    fn float_images_are_already_linear() {
+        let texture = load("exr", |path| {
+            Rgb32FImage::from_pixel(1, 1, Rgb([0.0, 0.5, 4.0])).save(path)
+        });
+        assert_eq!(value(&texture, [0.5, 0.5]), Color::new([0.0, 0.5, 4.0]));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    color::Color,
+    texture::{Texture, perlin::Perlin},
+    vec3::Point3,
+};
+
+/// How the Perlin noise is turned into a color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    /// Plain smooth noise
+    Smooth,
+
+    /// Sum of noise octaves, giving a cloudy or rocky look
+    Turbulence,
+
+    /// Sine stripes along z, phase shifted by turbulence
+    Marble,
+}
+
+/// Procedural texture based on Perlin noise
+pub struct NoiseTexture {
+    perlin: Perlin,
+    pattern: Pattern,
+
+    /// Frequency of the noise, higher values give smaller features
+    scale: f64,
+
+    /// Number of layers summed for turbulence
+    octaves: u32,
+
+    albedo: Color,
+}
+
+impl NoiseTexture {
+    pub fn new(seed: u64, pattern: Pattern, scale: f64, octaves: u32, albedo: Color) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            pattern,
+            scale,
+            octaves,
+            albedo,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _uv: [f64; 2], point: &Point3) -> Color {
+        let intensity = match self.pattern {
+            Pattern::Smooth => 0.5 * (1.0 + self.perlin.noise(&(self.scale * *point))),
+            Pattern::Turbulence => self.perlin.turbulence(&(self.scale * *point), self.octaves),
+            Pattern::Marble => {
+                let phase =
+                    self.scale * point.z() + 10.0 * self.perlin.turbulence(point, self.octaves);
+                0.5 * (1.0 + phase.sin())
+            }
+        };
+        intensity * self.albedo
+    }
+}
@@ -0,0 +1,148 @@
+use std::array;
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use crate::vec3::{Point3, Vec3};
+
+const POINT_COUNT: usize = 256;
+
+/// Perlin gradient noise, reproducible from its seed
+pub struct Perlin {
+    random_vectors: [Vec3; POINT_COUNT],
+    permutations: [[usize; POINT_COUNT]; 3],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let random_vectors = array::from_fn(|_| {
+            loop {
+                let vector = Vec3::new(array::from_fn(|_| rng.random_range(-1.0..1.0)));
+                let length_squared = vector.length_squared();
+                if 1e-160 < length_squared && length_squared <= 1.0 {
+                    break vector / length_squared.sqrt();
+                }
+            }
+        });
+        let permutations = array::from_fn(|_| {
+            let mut permutation = array::from_fn(|i| i);
+            permutation.shuffle(&mut rng);
+            permutation
+        });
+        Self {
+            random_vectors,
+            permutations,
+        }
+    }
+
+    /// Returns smooth noise in about `[-1, 1]`, trilinearly interpolating the gradients of the
+    /// surrounding lattice points with Hermite smoothing
+    pub fn noise(&self, point: &Point3) -> f64 {
+        let fraction: [f64; 3] = array::from_fn(|axis| point[axis] - point[axis].floor());
+        let cell: [i64; 3] = array::from_fn(|axis| point[axis].floor() as i64);
+
+        let mut gradients = [[[Vec3::default(); 2]; 2]; 2];
+        for (di, plane) in gradients.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, gradient) in row.iter_mut().enumerate() {
+                    let index = self.permutations[0][lattice_index(cell[0], di)]
+                        ^ self.permutations[1][lattice_index(cell[1], dj)]
+                        ^ self.permutations[2][lattice_index(cell[2], dk)];
+                    *gradient = self.random_vectors[index];
+                }
+            }
+        }
+        Self::interpolate(&gradients, fraction)
+    }
+
+    /// Sums `octaves` layers of noise, each at double the frequency and half the amplitude of
+    /// the previous one
+    pub fn turbulence(&self, point: &Point3, octaves: u32) -> f64 {
+        let mut accumulated = 0.0;
+        let mut point = *point;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accumulated += weight * self.noise(&point);
+            weight *= 0.5;
+            point *= 2.0;
+        }
+        accumulated.abs()
+    }
+
+    fn interpolate(gradients: &[[[Vec3; 2]; 2]; 2], fraction: [f64; 3]) -> f64 {
+        let smooth = fraction.map(|t| t * t * (3.0 - 2.0 * t));
+        let mut accumulated = 0.0;
+        for (i, plane) in gradients.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, gradient) in row.iter().enumerate() {
+                    let corner = [i, j, k];
+                    let weight =
+                        Vec3::new(array::from_fn(|axis| fraction[axis] - corner[axis] as f64));
+                    let falloff = (0..3)
+                        .map(|axis| {
+                            let c = corner[axis] as f64;
+                            c * smooth[axis] + (1.0 - c) * (1.0 - smooth[axis])
+                        })
+                        .product::<f64>();
+                    accumulated += falloff * gradient.dot(&weight);
+                }
+            }
+        }
+        accumulated
+    }
+}
+
+/// Wraps a lattice coordinate plus offset into the permutation table
+const fn lattice_index(cell: i64, offset: usize) -> usize {
+    (cell + offset as i64).rem_euclid(POINT_COUNT as i64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> impl Iterator<Item = Point3> {
+        (0..200).map(|i| {
+            let i = f64::from(i);
+            Point3::new([0.37 * i - 30.0, 1.13 * i.sin() * 50.0, 0.091 * i * i - 7.5])
+        })
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_noise() {
+        let (first, second) = (Perlin::new(7), Perlin::new(7));
+        for point in points() {
+            assert_eq!(first.noise(&point), second.noise(&point));
+            assert_eq!(first.turbulence(&point, 7), second.turbulence(&point, 7));
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_noise() {
+        let (first, second) = (Perlin::new(7), Perlin::new(8));
+        let differing = points()
+            .filter(|point| first.noise(point) != second.noise(point))
+            .count();
+        assert!(differing > 190, "{differing}");
+    }
+
+    #[test]
+    fn noise_stays_within_one() {
+        let perlin = Perlin::new(3);
+        let mut largest = 0.0_f64;
+        for point in points() {
+            for offset in 0..100 {
+                let noise = perlin.noise(&(point + Vec3::new([f64::from(offset) * 0.013; 3])));
+                largest = largest.max(noise.abs());
+            }
+        }
+        assert!(largest <= 1.0, "{largest}");
+        assert!(largest > 0.1, "{largest}");
+    }
+
+    #[test]
+    fn noise_vanishes_on_the_lattice() {
+        let perlin = Perlin::new(3);
+        assert_eq!(perlin.noise(&Point3::new([4.0, -2.0, 17.0])), 0.0);
+    }
+}
@@ -0,0 +1,17 @@
+use crate::{color::Color, texture::Texture, vec3::Point3};
+
+pub struct SolidColor {
+    albedo: Color,
+}
+
+impl SolidColor {
+    pub const fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _uv: [f64; 2], _point: &Point3) -> Color {
+        self.albedo
+    }
+}
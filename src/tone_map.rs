@@ -0,0 +1,190 @@
+//! Tone mapping, compressing linear high dynamic range colors into the displayable range
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::color::{Color, luminance};
+
+/// Operator mapping linear radiance to the `[0, 1]` range
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Operator {
+    /// Clip every channel to 1
+    #[default]
+    Clamp,
+
+    /// Reinhard's `L / (1 + L)` on luminance, which never reaches white
+    Reinhard,
+
+    /// Reinhard with the given luminance mapped to white
+    ExtendedReinhard { white: f64 },
+
+    /// Narkowicz's fit of the ACES filmic curve
+    Aces,
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Clamp => f.write_str("clamp"),
+            Self::Reinhard => f.write_str("reinhard"),
+            Self::ExtendedReinhard { white } => write!(f, "extended-reinhard:{white}"),
+            Self::Aces => f.write_str("aces"),
+        }
+    }
+}
+
+impl FromStr for Operator {
+    type Err = String;
+
+    /// Parses the operator names as displayed, the white point of `extended-reinhard` is
+    /// optional and defaults to 4.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "clamp" => Ok(Self::Clamp),
+            None if s == "reinhard" => Ok(Self::Reinhard),
+            None if s == "extended-reinhard" => Ok(Self::ExtendedReinhard { white: 4.0 }),
+            None if s == "aces" => Ok(Self::Aces),
+            Some(("extended-reinhard", white)) => match white.parse() {
+                Ok(white) if white > 0.0 => Ok(Self::ExtendedReinhard { white }),
+                _ => Err(format!("invalid white point `{white}`")),
+            },
+            _ => Err(format!("unknown tone mapping operator `{s}`")),
+        }
+    }
+}
+
+impl Operator {
+    pub fn apply(self, color: Color) -> Color {
+        match self {
+            Self::Clamp => map_channels(color, |value| value.clamp(0.0, 1.0)),
+            Self::Reinhard => scale_luminance(color, |luminance| luminance / (1.0 + luminance)),
+            Self::ExtendedReinhard { white } => scale_luminance(color, |luminance| {
+                luminance * (1.0 + luminance / (white * white)) / (1.0 + luminance)
+            }),
+            Self::Aces => map_channels(color, |value| {
+                // The fit expects the exposure used by the reference ACES transform
+                let value = 0.6 * value.max(0.0);
+                (value * (2.51 * value + 0.03) / (value * (2.43 * value + 0.59) + 0.14))
+                    .clamp(0.0, 1.0)
+            }),
+        }
+    }
+}
+
+fn map_channels(color: Color, f: impl Fn(f64) -> f64) -> Color {
+    Color::new([f(color.x()), f(color.y()), f(color.z())])
+}
+
+/// Maps the luminance of the color, keeping its hue, then clips what is still out of range
+fn scale_luminance(color: Color, f: impl Fn(f64) -> f64) -> Color {
+    let luminance = luminance(color);
+    if luminance <= 0.0 {
+        return Color::default();
+    }
+    map_channels(color * (f(luminance) / luminance), |value| {
+        value.clamp(0.0, 1.0)
+    })
+}
+
+/// Exposure adjustment followed by a tone mapping operator, applied before display encoding
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    pub operator: Operator,
+
+    /// Exposure compensation in stops (EV), every stop doubles the brightness
+    pub exposure: f64,
+}
+
+impl ToneMapping {
+    pub const fn new(operator: Operator, exposure: f64) -> Self {
+        Self { operator, exposure }
+    }
+
+    pub fn apply(&self, color: Color) -> Color {
+        self.operator.apply(color * self.exposure.exp2())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERATORS: [Operator; 4] = [
+        Operator::Clamp,
+        Operator::Reinhard,
+        Operator::ExtendedReinhard { white: 4.0 },
+        Operator::Aces,
+    ];
+
+    #[test]
+    fn black_stays_black() {
+        for operator in OPERATORS {
+            assert_eq!(
+                operator.apply(Color::default()),
+                Color::default(),
+                "{operator}"
+            );
+        }
+    }
+
+    #[test]
+    fn operators_are_monotonic() {
+        for operator in OPERATORS {
+            for hue in [Color::new([1.0; 3]), Color::new([0.2, 0.5, 1.0])] {
+                let mut previous = Color::default();
+                for i in 1..=1000 {
+                    let mapped = operator.apply(hue * (f64::from(i) / 50.0));
+                    for axis in 0..3 {
+                        assert!(mapped[axis] >= previous[axis], "{operator} {i}");
+                        assert!(mapped[axis] <= 1.0, "{operator} {i}");
+                    }
+                    previous = mapped;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extended_reinhard_maps_white_to_one() {
+        for white in [1.0, 4.0, 11.2] {
+            let mapped = Operator::ExtendedReinhard { white }.apply(Color::new([white; 3]));
+            assert!((mapped - Color::new([1.0; 3])).near_zero(), "{mapped:?}");
+        }
+        // Reinhard only approaches white
+        assert!(Operator::Reinhard.apply(Color::new([1000.0; 3])).x() < 1.0);
+    }
+
+    #[test]
+    fn every_stop_doubles_the_input() {
+        let color = Color::new([0.1, 0.2, 0.3]);
+        for operator in OPERATORS {
+            assert_eq!(
+                ToneMapping::new(operator, 1.0).apply(color),
+                operator.apply(2.0 * color)
+            );
+            assert_eq!(
+                ToneMapping::new(operator, -2.0).apply(color),
+                operator.apply(0.25 * color)
+            );
+            assert_eq!(
+                ToneMapping::new(operator, 0.0).apply(color),
+                operator.apply(color)
+            );
+        }
+    }
+
+    #[test]
+    fn operators_parse_as_displayed() {
+        for operator in OPERATORS {
+            assert_eq!(operator.to_string().parse(), Ok(operator));
+        }
+        assert_eq!(
+            "extended-reinhard".parse(),
+            Ok(Operator::ExtendedReinhard { white: 4.0 })
+        );
+        assert!("extended-reinhard:0".parse::<Operator>().is_err());
+        assert!("filmic".parse::<Operator>().is_err());
+    }
+}
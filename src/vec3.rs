@@ -1,5 +1,6 @@
 use std::{
     array,
+    f64::consts::PI,
     fmt::Display,
     iter::Sum,
     ops::{
@@ -7,14 +8,14 @@ use std::{
     },
 };
 
-use rand::{
-    distr::{Distribution, StandardUniform},
-    random_range,
-};
+use rand::distr::{Distribution, StandardUniform};
 
-use crate::color::Color;
+use crate::{
+    color::Color,
+    rng::{random, random_range},
+};
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Vec3([f64; 3]);
 
 impl Neg for Vec3 {
@@ -163,6 +164,16 @@ impl Vec3 {
         }
     }
 
+    /// Returns a random direction around +z, with a density proportional to the cosine of its
+    /// angle with +z
+    pub fn random_cosine_direction() -> Self {
+        let r1 = random::<f64>();
+        let r2 = random::<f64>();
+        let phi = 2.0 * PI * r1;
+        let sqrt_r2 = r2.sqrt();
+        Self([phi.cos() * sqrt_r2, phi.sin() * sqrt_r2, (1.0 - r2).sqrt()])
+    }
+
     pub fn random_on_hemisphere(normal: &Vec3) -> Vec3 {
         let on_unit_sphere = Self::random_unit_vector();
 
@@ -0,0 +1,479 @@
+//! Density grids for heterogeneous volumes, loaded from dense raw files or sparse leaf files.
+//!
+//! Both formats are little-endian binary. A raw file holds the resolution as three `u32`
+//! followed by one `f32` density per voxel, with x varying fastest, then y, then z.
+//!
+//! A sparse file follows the layout of NanoVDB leaves: the magic bytes `SPVX`, the resolution
+//! as three `u32`, the background density of inactive voxels as `f32` and the number of leaves
+//! as `u32`. Each leaf is the index of its 8×8×8 voxel block as three `u32`, followed by its
+//! 512 `f32` densities in the same order as raw files.
+
+use std::{
+    collections::HashMap,
+    error,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Magic bytes starting a sparse grid file
+const SPARSE_MAGIC: &[u8; 4] = b"SPVX";
+
+/// Number of voxels along each side of a sparse leaf
+const LEAF_SIZE: usize = 8;
+
+const LEAF_VOXELS: usize = LEAF_SIZE * LEAF_SIZE * LEAF_SIZE;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The grid file could not be read
+    Io { path: PathBuf, source: io::Error },
+
+    /// The grid file doesn't match its format
+    Format { path: PathBuf, message: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Format { path, message } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Format { .. } => None,
+        }
+    }
+}
+
+/// How the densities are stored
+#[derive(Debug, Clone)]
+enum Voxels {
+    Dense(Vec<f32>),
+
+    /// Blocks of 8×8×8 voxels by block index, voxels outside them have the background density
+    Sparse {
+        leaves: HashMap<[usize; 3], Box<[f32; LEAF_VOXELS]>>,
+        background: f32,
+    },
+}
+
+/// Grid of non-negative densities, filling the unit cube
+#[derive(Debug, Clone)]
+pub struct VoxelGrid {
+    resolution: [usize; 3],
+    voxels: Voxels,
+
+    /// Highest density in the grid, bounding every interpolated density
+    maximum: f64,
+}
+
+impl VoxelGrid {
+    /// Creates a dense grid from densities ordered x fastest, then y, then z.
+    ///
+    /// Panics if the number of densities doesn't match the resolution or a density is negative
+    /// or not finite.
+    pub fn new(resolution: [usize; 3], densities: Vec<f32>) -> Self {
+        assert_eq!(
+            densities.len(),
+            resolution.iter().product::<usize>(),
+            "densities must match the resolution"
+        );
+        assert!(
+            resolution.iter().all(|&size| size > 0),
+            "resolution must not be empty"
+        );
+        Self::validated(resolution, Voxels::Dense(densities))
+            .expect("densities must be finite and non-negative")
+    }
+
+    /// Checks the densities and finds their maximum
+    fn validated(resolution: [usize; 3], voxels: Voxels) -> Result<Self, String> {
+        let densities: Box<dyn Iterator<Item = &f32>> = match &voxels {
+            Voxels::Dense(densities) => Box::new(densities.iter()),
+            Voxels::Sparse { leaves, background } => Box::new(
+                leaves
+                    .values()
+                    .flat_map(|leaf| leaf.iter())
+                    .chain([background]),
+            ),
+        };
+        let mut maximum = 0.0_f32;
+        for &density in densities {
+            if !density.is_finite() || density < 0.0 {
+                return Err(format!(
+                    "densities must be finite and non-negative, found {density}"
+                ));
+            }
+            maximum = maximum.max(density);
+        }
+        Ok(Self {
+            resolution,
+            voxels,
+            maximum: maximum.into(),
+        })
+    }
+
+    /// Loads a dense grid from a raw file
+    pub fn load_raw(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut reader = Reader::open(path)?;
+        let resolution = reader.resolution()?;
+        let count = resolution.iter().product::<usize>();
+        let size = count
+            .checked_mul(size_of::<f32>())
+            .ok_or_else(|| reader.error(format!("resolution {resolution:?} is too large")))?;
+        // Check the size before allocating for the densities
+        if reader.bytes.len() - reader.offset != size {
+            return Err(reader.error(format!(
+                "expected {count} densities for resolution {resolution:?}"
+            )));
+        }
+        let densities = (0..count).map(|_| reader.f32()).collect::<Result<_, _>>()?;
+        reader.finish()?;
+        Self::validated(resolution, Voxels::Dense(densities))
+            .map_err(|message| reader.error(message))
+    }
+
+    /// Loads a sparse grid from a file of 8×8×8 leaves
+    pub fn load_sparse(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut reader = Reader::open(path)?;
+        if reader.bytes::<4>()? != *SPARSE_MAGIC {
+            return Err(reader.error("not a sparse grid file"));
+        }
+        let resolution = reader.resolution()?;
+        let background = reader.f32()?;
+        let leaf_count = reader.u32()?;
+        let mut leaves = HashMap::new();
+        for _ in 0..leaf_count {
+            let index = [reader.u32()?, reader.u32()?, reader.u32()?];
+            if (0..3).any(|axis| index[axis] * LEAF_SIZE >= resolution[axis]) {
+                return Err(reader.error(format!("leaf {index:?} is outside the grid")));
+            }
+            let mut leaf = Box::new([0.0; LEAF_VOXELS]);
+            for density in leaf.iter_mut() {
+                *density = reader.f32()?;
+            }
+            if leaves.insert(index, leaf).is_some() {
+                return Err(reader.error(format!("leaf {index:?} appears twice")));
+            }
+        }
+        reader.finish()?;
+        Self::validated(resolution, Voxels::Sparse { leaves, background })
+            .map_err(|message| reader.error(message))
+    }
+
+    pub const fn resolution(&self) -> [usize; 3] {
+        self.resolution
+    }
+
+    /// Returns the highest density in the grid, the majorant for tracking through it
+    pub const fn maximum(&self) -> f64 {
+        self.maximum
+    }
+
+    /// Returns the density of a voxel
+    fn voxel(&self, [x, y, z]: [usize; 3]) -> f32 {
+        match &self.voxels {
+            Voxels::Dense(densities) => {
+                densities[x + self.resolution[0] * (y + self.resolution[1] * z)]
+            }
+            Voxels::Sparse { leaves, background } => {
+                let leaf = [x / LEAF_SIZE, y / LEAF_SIZE, z / LEAF_SIZE];
+                leaves.get(&leaf).map_or(*background, |leaf| {
+                    let [x, y, z] = [x % LEAF_SIZE, y % LEAF_SIZE, z % LEAF_SIZE];
+                    leaf[x + LEAF_SIZE * (y + LEAF_SIZE * z)]
+                })
+            }
+        }
+    }
+
+    /// Returns the trilinearly interpolated density at a position in the unit cube, with voxel
+    /// centers at half-integer positions of the grid. Positions outside the cube are clamped.
+    pub fn density(&self, position: [f64; 3]) -> f64 {
+        let mut lower = [0; 3];
+        let mut upper = [0; 3];
+        let mut fraction = [0.0; 3];
+        for axis in 0..3 {
+            let size = self.resolution[axis];
+            let coordinate = (position[axis] * size as f64 - 0.5).clamp(0.0, (size - 1) as f64);
+            lower[axis] = coordinate as usize;
+            upper[axis] = (lower[axis] + 1).min(size - 1);
+            fraction[axis] = coordinate - lower[axis] as f64;
+        }
+
+        let mut density = 0.0;
+        for corner in 0..8 {
+            let mut weight = 1.0;
+            let mut index = [0; 3];
+            for axis in 0..3 {
+                if corner >> axis & 1 == 0 {
+                    index[axis] = lower[axis];
+                    weight *= 1.0 - fraction[axis];
+                } else {
+                    index[axis] = upper[axis];
+                    weight *= fraction[axis];
+                }
+            }
+            if weight > 0.0 {
+                density += weight * f64::from(self.voxel(index));
+            }
+        }
+        density
+    }
+}
+
+/// Reads little-endian values from a whole file, keeping track of the path for errors
+struct Reader<'a> {
+    path: &'a Path,
+    bytes: Vec<u8>,
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn open(path: &'a Path) -> Result<Self, Error> {
+        let bytes = fs::read(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Ok(Self {
+            path,
+            bytes,
+            offset: 0,
+        })
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::Format {
+            path: self.path.to_owned(),
+            message: message.into(),
+        }
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + N)
+            .ok_or_else(|| self.error("unexpected end of file"))?;
+        self.offset += N;
+        Ok(bytes.try_into().expect("slice has N bytes"))
+    }
+
+    fn u32(&mut self) -> Result<usize, Error> {
+        Ok(u32::from_le_bytes(self.bytes()?) as usize)
+    }
+
+    fn f32(&mut self) -> Result<f32, Error> {
+        Ok(f32::from_le_bytes(self.bytes()?))
+    }
+
+    fn resolution(&mut self) -> Result<[usize; 3], Error> {
+        let resolution = [self.u32()?, self.u32()?, self.u32()?];
+        if resolution.contains(&0) {
+            return Err(self.error(format!(
+                "resolution must not be empty, found {resolution:?}"
+            )));
+        }
+        if resolution
+            .iter()
+            .try_fold(1_usize, |count, &size| count.checked_mul(size))
+            .is_none()
+        {
+            return Err(self.error(format!("resolution {resolution:?} is too large")));
+        }
+        Ok(resolution)
+    }
+
+    /// Checks that all bytes were read
+    fn finish(&self) -> Result<(), Error> {
+        if self.offset == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(self.error(format!(
+                "{} unexpected bytes at the end of the file",
+                self.bytes.len() - self.offset
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the bytes of a grid file from its magic bytes, integers and densities, in order
+    struct Bytes(Vec<u8>);
+
+    impl Bytes {
+        fn new() -> Self {
+            Self(Vec::new())
+        }
+
+        fn magic(mut self) -> Self {
+            self.0.extend(SPARSE_MAGIC);
+            self
+        }
+
+        fn u32s(mut self, values: &[u32]) -> Self {
+            self.0
+                .extend(values.iter().flat_map(|value| value.to_le_bytes()));
+            self
+        }
+
+        fn f32s(mut self, values: impl IntoIterator<Item = f32>) -> Self {
+            self.0
+                .extend(values.into_iter().flat_map(|value| value.to_le_bytes()));
+            self
+        }
+    }
+
+    /// Writes the bytes to a temporary file and loads it with the loader
+    fn load(
+        name: &str,
+        bytes: Bytes,
+        loader: fn(&Path) -> Result<VoxelGrid, Error>,
+    ) -> Result<VoxelGrid, Error> {
+        let path =
+            std::env::temp_dir().join(format!("voxel_grid_{name}_{}.bin", std::process::id()));
+        fs::write(&path, bytes.0).unwrap();
+        let result = loader(&path);
+        fs::remove_file(&path).unwrap();
+        result
+    }
+
+    fn load_raw(name: &str, bytes: Bytes) -> Result<VoxelGrid, Error> {
+        load(name, bytes, |path| VoxelGrid::load_raw(path))
+    }
+
+    fn load_sparse(name: &str, bytes: Bytes) -> Result<VoxelGrid, Error> {
+        load(name, bytes, |path| VoxelGrid::load_sparse(path))
+    }
+
+    /// Returns the message of a format error
+    fn format_error(result: Result<VoxelGrid, Error>) -> String {
+        match result {
+            Err(Error::Format { message, .. }) => message,
+            Err(error) => panic!("expected a format error, found {error}"),
+            Ok(_) => panic!("expected a format error"),
+        }
+    }
+
+    /// Header of a sparse file with a single leaf at the given index
+    fn sparse_header(resolution: [u32; 3], leaves: &[[u32; 3]]) -> Bytes {
+        let mut bytes = Bytes::new()
+            .magic()
+            .u32s(&resolution)
+            .f32s([0.25])
+            .u32s(&[leaves.len() as u32]);
+        for leaf in leaves {
+            bytes = bytes.u32s(leaf).f32s((0..LEAF_VOXELS).map(|i| i as f32));
+        }
+        bytes
+    }
+
+    #[test]
+    fn raw_grid_round_trips() {
+        let grid = load_raw(
+            "raw",
+            Bytes::new()
+                .u32s(&[2, 3, 4])
+                .f32s((0..24).map(|i| i as f32)),
+        )
+        .unwrap();
+        assert_eq!(grid.resolution(), [2, 3, 4]);
+        assert_eq!(grid.maximum(), 23.0);
+        assert_eq!(grid.voxel([1, 0, 0]), 1.0);
+        assert_eq!(grid.voxel([0, 2, 0]), 4.0);
+        assert_eq!(grid.voxel([1, 2, 3]), 23.0);
+        // Voxel centers are at half-integer grid positions
+        assert!((grid.density([0.75, 0.5 / 3.0, 0.125]) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sparse_grid_round_trips() {
+        let grid = load_sparse("sparse", sparse_header([16, 8, 8], &[[1, 0, 0]])).unwrap();
+        assert_eq!(grid.resolution(), [16, 8, 8]);
+        assert_eq!(grid.maximum(), (LEAF_VOXELS - 1) as f64);
+        assert_eq!(grid.voxel([3, 3, 3]), 0.25);
+        assert_eq!(grid.voxel([8, 0, 0]), 0.0);
+        assert_eq!(grid.voxel([9, 2, 1]), (1 + 8 * (2 + 8)) as f32);
+    }
+
+    #[test]
+    fn sparse_files_need_the_magic_bytes() {
+        let message = format_error(load_sparse("magic", Bytes::new().u32s(&[8, 8, 8, 0, 0])));
+        assert_eq!(message, "not a sparse grid file");
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        let message = format_error(load_raw(
+            "raw_truncated",
+            Bytes::new().u32s(&[2, 2, 2]).f32s([1.0; 7]),
+        ));
+        assert_eq!(message, "expected 8 densities for resolution [2, 2, 2]");
+        let mut bytes = sparse_header([8, 8, 8], &[[0, 0, 0]]);
+        bytes.0.truncate(bytes.0.len() - 2);
+        let message = format_error(load_sparse("sparse_truncated", bytes));
+        assert_eq!(message, "unexpected end of file");
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let message = format_error(load_raw(
+            "raw_trailing",
+            Bytes::new().u32s(&[2, 2, 2]).f32s([1.0; 9]),
+        ));
+        assert_eq!(message, "expected 8 densities for resolution [2, 2, 2]");
+        let message = format_error(load_sparse(
+            "sparse_trailing",
+            sparse_header([8, 8, 8], &[]).u32s(&[7]),
+        ));
+        assert_eq!(message, "4 unexpected bytes at the end of the file");
+    }
+
+    #[test]
+    fn leaves_must_be_inside_the_grid_once() {
+        let message = format_error(load_sparse(
+            "outside",
+            sparse_header([16, 8, 8], &[[0, 1, 0]]),
+        ));
+        assert_eq!(message, "leaf [0, 1, 0] is outside the grid");
+        let message = format_error(load_sparse(
+            "duplicate",
+            sparse_header([16, 8, 8], &[[1, 0, 0], [1, 0, 0]]),
+        ));
+        assert_eq!(message, "leaf [1, 0, 0] appears twice");
+    }
+
+    #[test]
+    fn densities_must_be_non_negative() {
+        let message = format_error(load_raw(
+            "negative",
+            Bytes::new().u32s(&[1, 1, 2]).f32s([1.0, -1.0]),
+        ));
+        assert_eq!(
+            message,
+            "densities must be finite and non-negative, found -1"
+        );
+    }
+
+    #[test]
+    fn resolution_must_be_reasonable() {
+        let message = format_error(load_raw("empty", Bytes::new().u32s(&[2, 0, 2])));
+        assert_eq!(message, "resolution must not be empty, found [2, 0, 2]");
+        let huge = [u32::MAX; 3];
+        let message = format_error(load_raw("overflow", Bytes::new().u32s(&huge)));
+        assert_eq!(message, format!("resolution {huge:?} is too large"));
+        // The voxel count fits, but not the size of its densities in bytes
+        let large = [1 << 31, 1 << 31, 2];
+        let message = format_error(load_raw("bytes", Bytes::new().u32s(&large)));
+        assert_eq!(message, format!("resolution {large:?} is too large"));
+    }
+}